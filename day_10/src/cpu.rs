@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{instruction::{Instruction, Opcode, Operand, DEFAULT_REGISTER}, observer::CycleObserver, program::Program};
+
+// How many cycles each kind of instruction takes. Every instruction takes at least one.
+#[derive(Debug, Clone)]
+pub struct CycleCosts {
+    costs: HashMap<Opcode, u32>
+}
+
+impl Default for CycleCosts {
+    fn default() -> Self {
+        let costs = HashMap::from([
+            (Opcode::NoOp, 1),
+            (Opcode::Add, 2),
+            (Opcode::Sub, 2),
+            (Opcode::Mul, 2),
+            (Opcode::Mov, 1),
+            (Opcode::Jump, 1),
+            (Opcode::JumpIfZero, 1),
+            (Opcode::JumpIfNotZero, 1),
+            (Opcode::JumpIfPositive, 1),
+            (Opcode::JumpIfNegative, 1)
+        ]);
+        CycleCosts { costs }
+    }
+}
+
+impl CycleCosts {
+    pub fn with_cost(mut self, opcode: Opcode, cycles: u32) -> Self {
+        self.costs.insert(opcode, cycles.max(1));
+        self
+    }
+
+    pub fn cycles_for(&self, instruction: &Instruction) -> u32 {
+        self.costs.get(&instruction.opcode()).copied().unwrap_or(1)
+    }
+}
+
+pub struct Cpu {
+    pub registers: BTreeMap<String, i32>,
+    // Index into the program of the next instruction to fetch
+    pub instruction_pointer: usize,
+    pub current_instruction: Option<Instruction>,
+    pub remaining_cycles_for_current_instruction: u32,
+    pub current_cycle: usize,
+    pub cycle_costs: CycleCosts,
+    // Stops programs that loop forever
+    pub cycle_limit: Option<usize>
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        let mut cpu = Cpu {
+            registers: BTreeMap::new(),
+            instruction_pointer: 0,
+            current_instruction: None,
+            remaining_cycles_for_current_instruction: 0,
+            current_cycle: 0,
+            cycle_costs: CycleCosts::default(),
+            cycle_limit: None
+        };
+        cpu.reset();
+        cpu
+    }
+}
+
+impl Cpu {
+    pub fn with_cycle_costs(mut self, cycle_costs: CycleCosts) -> Self {
+        self.cycle_costs = cycle_costs;
+        self
+    }
+
+    pub fn with_cycle_limit(mut self, cycle_limit: usize) -> Self {
+        self.cycle_limit = Some(cycle_limit);
+        self
+    }
+
+    pub fn reset(&mut self) {
+        self.registers = BTreeMap::from([(DEFAULT_REGISTER.to_string(), 1)]);
+        self.instruction_pointer = 0;
+        self.current_instruction = None;
+        self.remaining_cycles_for_current_instruction = 0;
+        self.current_cycle = 0;
+    }
+
+    // Registers that have never been written read as zero, apart from `x` which starts at one.
+    pub fn register(&self, name: &str) -> i32 {
+        self.registers.get(name).copied().unwrap_or(0)
+    }
+
+    // Runs the program from the start, showing every cycle to each observer in turn.
+    pub fn run(&mut self, program: &Program, observers: &mut [&mut dyn CycleObserver]) {
+        self.reset();
+        while self.begin_cycle(program) {
+            for observer in observers.iter_mut() {
+                observer.observe(self);
+            }
+            self.end_cycle(program);
+        }
+    }
+
+    // Whether the program ran off its end, rather than being stopped by the cycle limit.
+    pub fn finished(&self, program: &Program) -> bool {
+        self.current_instruction.is_none() && self.instruction_pointer >= program.instructions.len()
+    }
+
+    // Starts the next cycle, fetching an instruction if the last one has finished. Returns false
+    // once the program has run off its end or the cycle limit has been reached.
+    pub fn begin_cycle(&mut self, program: &Program) -> bool {
+        if self.cycle_limit.is_some_and(|limit| self.current_cycle >= limit) {
+            return false;
+        }
+        if self.current_instruction.is_none() {
+            match program.instructions.get(self.instruction_pointer) {
+                Some(instruction) => {
+                    self.remaining_cycles_for_current_instruction = self.cycle_costs.cycles_for(instruction);
+                    self.current_instruction = Some(instruction.clone());
+                    self.instruction_pointer += 1;
+                }
+                None => return false
+            }
+        }
+        self.current_cycle += 1;
+        true
+    }
+
+    // Finishes the current cycle. An instruction only takes effect at the end of its last cycle.
+    pub fn end_cycle(&mut self, program: &Program) {
+        self.remaining_cycles_for_current_instruction -= 1;
+        if self.remaining_cycles_for_current_instruction == 0 {
+            let instruction = self.current_instruction.take().unwrap();
+            self.execute(&instruction, program);
+        }
+    }
+
+    fn execute(&mut self, instruction: &Instruction, program: &Program) {
+        match instruction {
+            Instruction::NoOp => (),
+            Instruction::Add(register, operand) => self.apply(register, operand, i32::wrapping_add),
+            Instruction::Sub(register, operand) => self.apply(register, operand, i32::wrapping_sub),
+            Instruction::Mul(register, operand) => self.apply(register, operand, i32::wrapping_mul),
+            Instruction::Mov(register, operand) => self.apply(register, operand, |_, value| value),
+            Instruction::Jump(condition, label) => {
+                if condition.holds(condition.register().map_or(0, |register| self.register(register))) {
+                    self.instruction_pointer = program.label_target(label);
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, register: &str, operand: &Operand, operation: fn(i32, i32) -> i32) {
+        let value = match operand {
+            Operand::Register(source) => self.register(source),
+            Operand::Value(value) => *value
+        };
+        let result = operation(self.register(register), value);
+        self.registers.insert(register.to_string(), result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::observer::SignalSampler;
+
+    const COUNTDOWN: &str = "mov a 3\nloop:\naddx 2\nsubx a, 1\njnz a loop\n";
+
+    #[test]
+    fn runs_a_jnz_loop_to_the_end() {
+        let program = Program::from_str(COUNTDOWN).unwrap();
+        let mut cpu = Cpu::default();
+        cpu.run(&program, &mut []);
+
+        assert!(cpu.finished(&program));
+        assert_eq!((cpu.register("x"), cpu.register("a")), (7, 0));
+        // One for the mov, then three passes of addx (2), subx (2) and jnz (1)
+        assert_eq!(cpu.current_cycle, 16);
+    }
+
+    #[test]
+    fn cost_overrides_change_timing() {
+        let program = Program::from_str(COUNTDOWN).unwrap();
+        let mut sampler = SignalSampler::new(vec![4]);
+        Cpu::default().run(&program, &mut [&mut sampler]);
+        // The first addx has finished by cycle 4
+        assert_eq!(sampler.strengths, vec![4 * 3]);
+
+        let mut sampler = SignalSampler::new(vec![4]);
+        let mut cpu = Cpu::default().with_cycle_costs(CycleCosts::default().with_cost(Opcode::Add, 5));
+        cpu.run(&program, &mut [&mut sampler]);
+        assert_eq!(sampler.strengths, vec![4]);
+        assert_eq!(cpu.current_cycle, 1 + 3 * (5 + 2 + 1));
+        assert_eq!(cpu.register("x"), 7);
+    }
+
+    #[test]
+    fn cycle_limit_stops_endless_loops() {
+        let program = Program::from_str("loop: \njmp loop").unwrap();
+        let mut cpu = Cpu::default().with_cycle_limit(1000);
+        cpu.run(&program, &mut []);
+        assert_eq!(cpu.current_cycle, 1000);
+        assert!(!cpu.finished(&program));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{cpu::Cpu, instruction::DEFAULT_REGISTER, observer::CycleObserver};
+
+// Lit and unlit pixels, row by row from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PixelBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<bool>
+}
+
+impl PixelBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        PixelBuffer { width, height, pixels: vec![false; width * height] }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    // `#` for lit and `.` for unlit, the way the puzzle draws it.
+    pub fn to_text(&self) -> String {
+        (0..self.height)
+            .map(|y| (0..self.width).map(|x| if self.get(x, y) { '#' } else { '.' }).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Plain (P1) PBM, where 1 is a black pixel, so lit pixels come out dark on a light background.
+    pub fn to_pbm(&self) -> String {
+        let mut pbm = format!("P1\n{} {}\n", self.width, self.height);
+        for y in 0..self.height {
+            let row = (0..self.width).map(|x| if self.get(x, y) { "1" } else { "0" }).collect::<Vec<_>>();
+            pbm.push_str(&row.join(" "));
+            pbm.push('\n');
+        }
+        pbm
+    }
+}
+
+// Draws one pixel per cycle, left to right and top to bottom, lighting it when the sprite covers
+// its column. The sprite is `sprite_width` pixels wide and centred on `x`, leaning right when the
+// width is even. Cycles after the last pixel are ignored.
+#[derive(Debug, Clone)]
+pub struct Crt {
+    sprite_width: usize,
+    drawn: usize,
+    screen: PixelBuffer
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Crt::new(40, 6, 3)
+    }
+}
+
+impl Crt {
+    pub fn new(width: usize, height: usize, sprite_width: usize) -> Self {
+        Crt { sprite_width, drawn: 0, screen: PixelBuffer::new(width, height) }
+    }
+
+    pub fn screen(&self) -> &PixelBuffer {
+        &self.screen
+    }
+}
+
+impl CycleObserver for Crt {
+    fn observe(&mut self, cpu: &Cpu) {
+        if self.drawn == self.screen.pixels.len() {
+            return;
+        }
+        let column = (self.drawn % self.screen.width) as i64;
+        let sprite_start = cpu.register(DEFAULT_REGISTER) as i64 - (self.sprite_width as i64 - 1) / 2;
+        self.screen.pixels[self.drawn] = (sprite_start..sprite_start + self.sprite_width as i64).contains(&column);
+        self.drawn += 1;
+    }
+}
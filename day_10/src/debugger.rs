@@ -0,0 +1,404 @@
+use std::{collections::BTreeMap, fmt, io::{self, BufRead, Write}, str::FromStr};
+
+use crate::{cpu::Cpu, program::Program};
+
+#[derive(Debug, Clone, Copy)]
+pub struct GenericParseErr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual
+}
+
+impl Comparison {
+    fn holds(&self, left: i32, right: i32) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">="
+        }
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = GenericParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "==" => Ok(Comparison::Equal),
+            "!=" => Ok(Comparison::NotEqual),
+            "<" => Ok(Comparison::Less),
+            "<=" => Ok(Comparison::LessOrEqual),
+            ">" => Ok(Comparison::Greater),
+            ">=" => Ok(Comparison::GreaterOrEqual),
+            _ => Err(GenericParseErr)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    Cycle(usize),
+    // Fires on the cycle the comparison becomes true, rather than on every cycle it stays true
+    Register { register: String, comparison: Comparison, value: i32 }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Cycle(cycle) => write!(f, "cycle {}", cycle),
+            Breakpoint::Register { register, comparison, value } => write!(f, "{} {} {}", register, comparison.symbol(), value)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    StepCycle(usize),
+    StepInstruction(usize),
+    Continue,
+    Break(Breakpoint),
+    Watch(String),
+    Delete(usize),
+    List,
+    Print,
+    Help,
+    Quit
+}
+
+// `step [n]`, `next [n]`, `continue`, `break 20`, `break x >= 5`, `watch x`, `delete 1`, `list`,
+// `print`, `help` and `quit`, or just their first letter.
+impl FromStr for Command {
+    type Err = GenericParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        let count = || match words.get(1) {
+            Some(count) => usize::from_str(count).map_err(|_| GenericParseErr),
+            None => Ok(1)
+        };
+        match words.as_slice() {
+            ["step" | "s", ..] => count().map(Command::StepCycle),
+            ["next" | "n", ..] => count().map(Command::StepInstruction),
+            ["continue" | "c"] => Ok(Command::Continue),
+            ["break" | "b", cycle] => usize::from_str(cycle).map(|cycle| Command::Break(Breakpoint::Cycle(cycle))).map_err(|_| GenericParseErr),
+            ["break" | "b", register, comparison, value] => Ok(Command::Break(Breakpoint::Register {
+                register: register.to_string(),
+                comparison: Comparison::from_str(comparison)?,
+                value: i32::from_str(value).map_err(|_| GenericParseErr)?
+            })),
+            ["watch" | "w", register] => Ok(Command::Watch(register.to_string())),
+            ["delete" | "d", number] => usize::from_str(number).map(Command::Delete).map_err(|_| GenericParseErr),
+            ["list" | "l"] => Ok(Command::List),
+            ["print" | "p"] => Ok(Command::Print),
+            ["help" | "h"] => Ok(Command::Help),
+            ["quit" | "q"] => Ok(Command::Quit),
+            _ => Err(GenericParseErr)
+        }
+    }
+}
+
+// Why execution stopped before it was asked to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    Breakpoint(usize),
+    Watchpoint { register: String, old: i32, new: i32 },
+    Halted
+}
+
+// Runs a program a cycle at a time. Between commands the CPU is paused part way through a cycle,
+// after the instruction has been fetched and before it takes effect, which is when the puzzle
+// samples the register. Breakpoints and watchpoints are checked each time a cycle starts.
+pub struct Debugger<'a> {
+    pub cpu: Cpu,
+    program: &'a Program,
+    // Deleted breakpoints leave a gap so the others keep their numbers
+    breakpoints: Vec<Option<Breakpoint>>,
+    watchpoints: BTreeMap<String, i32>,
+    halted: bool
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(mut cpu: Cpu, program: &'a Program) -> Self {
+        cpu.reset();
+        let halted = !cpu.begin_cycle(program);
+        Debugger { cpu, program, breakpoints: vec![], watchpoints: BTreeMap::new(), halted }
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        self.breakpoints.push(Some(breakpoint));
+        self.breakpoints.len()
+    }
+
+    pub fn watch(&mut self, register: &str) {
+        self.watchpoints.insert(register.to_string(), self.cpu.register(register));
+    }
+
+    // Breakpoints are numbered from 1 in the order they were added.
+    pub fn delete_breakpoint(&mut self, number: usize) -> bool {
+        match number.checked_sub(1).and_then(|idx| self.breakpoints.get_mut(idx)) {
+            Some(breakpoint) => breakpoint.take().is_some(),
+            None => false
+        }
+    }
+
+    // Finishes the current cycle and starts the next one.
+    pub fn step_cycle(&mut self) -> Option<StopReason> {
+        if self.halted {
+            return Some(StopReason::Halted);
+        }
+        let before = self.breakpoints.iter().map(|breakpoint| breakpoint.as_ref().is_some_and(|b| self.hit(b))).collect::<Vec<_>>();
+        self.cpu.end_cycle(self.program);
+        if !self.cpu.begin_cycle(self.program) {
+            self.halted = true;
+            return Some(StopReason::Halted);
+        }
+
+        let breakpoint = self.breakpoints.iter()
+            .zip(before)
+            .position(|(breakpoint, was_hit)| !was_hit && breakpoint.as_ref().is_some_and(|b| self.hit(b)))
+            .map(|idx| StopReason::Breakpoint(idx + 1));
+
+        // Every watched value is brought up to date, even when only the first change is reported
+        let mut watchpoint = None;
+        for (register, old) in self.watchpoints.iter_mut() {
+            let new = self.cpu.registers.get(register).copied().unwrap_or(0);
+            if new != *old {
+                watchpoint.get_or_insert(StopReason::Watchpoint { register: register.clone(), old: *old, new });
+                *old = new;
+            }
+        }
+        breakpoint.or(watchpoint)
+    }
+
+    // Runs until the instruction in flight has taken effect and the next one has been fetched.
+    pub fn step_instruction(&mut self) -> Option<StopReason> {
+        loop {
+            let finishing = self.cpu.remaining_cycles_for_current_instruction == 1;
+            if let Some(reason) = self.step_cycle() {
+                return Some(reason);
+            }
+            if finishing {
+                return None;
+            }
+        }
+    }
+
+    pub fn run(&mut self) -> StopReason {
+        loop {
+            if let Some(reason) = self.step_cycle() {
+                return reason;
+            }
+        }
+    }
+
+    pub fn state(&self) -> String {
+        let registers = self.cpu.registers.iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let in_flight = match &self.cpu.current_instruction {
+            Some(instruction) if !self.halted => {
+                let line_number = self.program.line_numbers[self.cpu.instruction_pointer - 1];
+                format!("line {}: {} (cycles left including this one: {})", line_number, instruction, self.cpu.remaining_cycles_for_current_instruction)
+            }
+            _ => "halted".to_string()
+        };
+        format!("cycle {} | {} | {}", self.cpu.current_cycle, registers, in_flight)
+    }
+
+    // Reads commands from `input` until it runs out or `quit`, printing the state after each one.
+    pub fn repl<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        writeln!(output, "{}", self.state())?;
+        write!(output, "(debug) ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                write!(output, "(debug) ")?;
+                output.flush()?;
+                continue;
+            }
+
+            let stop = match Command::from_str(&line) {
+                Ok(Command::StepCycle(count)) => (0..count).find_map(|_| self.step_cycle()),
+                Ok(Command::StepInstruction(count)) => (0..count).find_map(|_| self.step_instruction()),
+                Ok(Command::Continue) => Some(self.run()),
+                Ok(Command::Break(breakpoint)) => {
+                    let description = breakpoint.to_string();
+                    writeln!(output, "Breakpoint {} at {}", self.add_breakpoint(breakpoint), description)?;
+                    None
+                }
+                Ok(Command::Watch(register)) => {
+                    self.watch(&register);
+                    writeln!(output, "Watching {}", register)?;
+                    None
+                }
+                Ok(Command::Delete(number)) => {
+                    if !self.delete_breakpoint(number) {
+                        writeln!(output, "No breakpoint {}", number)?;
+                    }
+                    None
+                }
+                Ok(Command::List) => {
+                    for (idx, breakpoint) in self.breakpoints.iter().enumerate() {
+                        if let Some(breakpoint) = breakpoint {
+                            writeln!(output, "Breakpoint {} at {}", idx + 1, breakpoint)?;
+                        }
+                    }
+                    for register in self.watchpoints.keys() {
+                        writeln!(output, "Watching {}", register)?;
+                    }
+                    None
+                }
+                Ok(Command::Print) => None,
+                Ok(Command::Help) => {
+                    writeln!(output, "step [n], next [n], continue, break <cycle>, break <register> <op> <value>, watch <register>, delete <n>, list, print, quit")?;
+                    None
+                }
+                Ok(Command::Quit) => return Ok(()),
+                Err(_) => {
+                    writeln!(output, "Unrecognised command, try help")?;
+                    None
+                }
+            };
+
+            match stop {
+                Some(StopReason::Breakpoint(number)) => writeln!(output, "Hit breakpoint {} ({})", number, self.breakpoints[number - 1].as_ref().unwrap())?,
+                Some(StopReason::Watchpoint { register, old, new }) => writeln!(output, "{} changed from {} to {}", register, old, new)?,
+                Some(StopReason::Halted) => writeln!(output, "Program finished")?,
+                None => {}
+            }
+            writeln!(output, "{}", self.state())?;
+            write!(output, "(debug) ")?;
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+
+    fn hit(&self, breakpoint: &Breakpoint) -> bool {
+        match breakpoint {
+            Breakpoint::Cycle(cycle) => self.cpu.current_cycle == *cycle,
+            Breakpoint::Register { register, comparison, value } => comparison.holds(self.cpu.register(register), *value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    // x is 1 for cycles 1-3, 4 for cycles 4-5 and 2 from cycle 6, and a becomes 5 on cycle 7
+    const PROGRAM: &str = "noop\naddx 3\naddx -2\nmov a 5\nnoop";
+
+    fn x_at_least(value: i32) -> Breakpoint {
+        Breakpoint::Register { register: "x".to_string(), comparison: Comparison::GreaterOrEqual, value }
+    }
+
+    #[test]
+    fn breakpoints_fire_when_their_condition_becomes_true() {
+        let program = Program::from_str(PROGRAM).unwrap();
+        let mut debugger = Debugger::new(Cpu::default(), &program);
+        assert_eq!(debugger.add_breakpoint(x_at_least(3)), 1);
+        assert_eq!(debugger.add_breakpoint(Breakpoint::Cycle(6)), 2);
+
+        assert_eq!(debugger.run(), StopReason::Breakpoint(1));
+        assert_eq!((debugger.cpu.current_cycle, debugger.cpu.register("x")), (4, 4));
+        // x is still at least 3 on cycle 5, which doesn't count as hitting it again
+        assert_eq!(debugger.run(), StopReason::Breakpoint(2));
+        assert_eq!(debugger.cpu.current_cycle, 6);
+        assert_eq!(debugger.run(), StopReason::Halted);
+        assert_eq!(debugger.step_cycle(), Some(StopReason::Halted));
+
+        let mut debugger = Debugger::new(Cpu::default(), &program);
+        debugger.add_breakpoint(x_at_least(3));
+        assert!(debugger.delete_breakpoint(1));
+        assert!(!debugger.delete_breakpoint(1));
+        assert!(!debugger.delete_breakpoint(0));
+        assert_eq!(debugger.run(), StopReason::Halted);
+    }
+
+    #[test]
+    fn step_instruction_stops_at_instruction_boundaries() {
+        let program = Program::from_str(PROGRAM).unwrap();
+        let mut debugger = Debugger::new(Cpu::default(), &program);
+        let mut stops = vec![debugger.cpu.current_cycle];
+        while debugger.step_instruction().is_none() {
+            // Each stop has just fetched an instruction, so all of its cycles are still to come
+            let in_flight = debugger.cpu.current_instruction.as_ref().unwrap();
+            assert_eq!(debugger.cpu.remaining_cycles_for_current_instruction, debugger.cpu.cycle_costs.cycles_for(in_flight));
+            stops.push(debugger.cpu.current_cycle);
+        }
+        assert_eq!(stops, vec![1, 2, 4, 6, 7]);
+        assert_eq!((debugger.cpu.register("x"), debugger.cpu.register("a")), (2, 5));
+    }
+
+    #[test]
+    fn watchpoints_keep_every_watched_value_up_to_date() {
+        let program = Program::from_str(PROGRAM).unwrap();
+        let mut debugger = Debugger::new(Cpu::default(), &program);
+        debugger.watch("x");
+        debugger.watch("a");
+
+        let changed = |register: &str, old, new| Some(StopReason::Watchpoint { register: register.to_string(), old, new });
+        assert_eq!(debugger.step_cycle(), None);
+        assert_eq!(debugger.step_cycle(), None);
+        assert_eq!(debugger.step_cycle(), changed("x", 1, 4));
+        assert_eq!(debugger.cpu.current_cycle, 4);
+
+        // Both change at once, so only the first is reported but neither is reported again
+        debugger.cpu.registers.insert("a".to_string(), 10);
+        debugger.cpu.registers.insert("x".to_string(), 20);
+        assert_eq!(debugger.step_cycle(), changed("a", 0, 10));
+        assert_eq!(debugger.run(), StopReason::Watchpoint { register: "x".to_string(), old: 20, new: 18 });
+        assert_eq!(debugger.run(), StopReason::Watchpoint { register: "a".to_string(), old: 10, new: 5 });
+        assert_eq!(debugger.run(), StopReason::Halted);
+    }
+
+    #[test]
+    fn repl_prints_the_state_after_each_command() {
+        let program = Program::from_str(PROGRAM).unwrap();
+        let mut debugger = Debugger::new(Cpu::default(), &program);
+        let mut output = vec![];
+        debugger.repl(Cursor::new("break x >= 3\ncontinue\nbogus\n\nnext\nquit\nstep\n"), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().map(|line| line.trim_start_matches("(debug) ")).collect::<Vec<_>>();
+        assert_eq!(lines, vec![
+            "cycle 1 | x=1 | line 1: noop (cycles left including this one: 1)",
+            "Breakpoint 1 at x >= 3",
+            "cycle 1 | x=1 | line 1: noop (cycles left including this one: 1)",
+            "Hit breakpoint 1 (x >= 3)",
+            "cycle 4 | x=4 | line 3: addx x -2 (cycles left including this one: 2)",
+            "Unrecognised command, try help",
+            "cycle 4 | x=4 | line 3: addx x -2 (cycles left including this one: 2)",
+            "cycle 6 | x=2 | line 4: mov a 5 (cycles left including this one: 1)",
+            // The prompt `quit` was typed at
+            ""
+        ]);
+        // Quitting stops reading, so the last step never ran
+        assert_eq!(debugger.cpu.current_cycle, 6);
+    }
+}
@@ -0,0 +1,273 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstructionErr {
+    UnknownInstruction(String),
+    MissingOperand,
+    InvalidOperand(String),
+    TrailingOperand(String)
+}
+
+// The register the puzzle's single-register instructions (`addx 5`) work on.
+pub const DEFAULT_REGISTER: &str = "x";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    Register(String),
+    Value(i32)
+}
+
+impl FromStr for Operand {
+    type Err = InstructionErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(value) = i32::from_str(s) {
+            return Ok(Operand::Value(value));
+        }
+        parse_register(s).map(Operand::Register)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Always,
+    Zero(String),
+    NotZero(String),
+    Positive(String),
+    Negative(String)
+}
+
+impl Condition {
+    // The register the condition looks at, if any.
+    pub fn register(&self) -> Option<&str> {
+        match self {
+            Condition::Always => None,
+            Condition::Zero(r) | Condition::NotZero(r) | Condition::Positive(r) | Condition::Negative(r) => Some(r)
+        }
+    }
+
+    pub fn holds(&self, value: i32) -> bool {
+        match self {
+            Condition::Always => true,
+            Condition::Zero(_) => value == 0,
+            Condition::NotZero(_) => value != 0,
+            Condition::Positive(_) => value > 0,
+            Condition::Negative(_) => value < 0
+        }
+    }
+}
+
+// What an instruction is, without its operands. This is what cycle costs are looked up by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Opcode {
+    NoOp,
+    Add,
+    Sub,
+    Mul,
+    Mov,
+    Jump,
+    JumpIfZero,
+    JumpIfNotZero,
+    JumpIfPositive,
+    JumpIfNegative
+}
+
+impl FromStr for Opcode {
+    type Err = InstructionErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "noop" => Ok(Opcode::NoOp),
+            "addx" => Ok(Opcode::Add),
+            "subx" => Ok(Opcode::Sub),
+            "mulx" => Ok(Opcode::Mul),
+            "mov" => Ok(Opcode::Mov),
+            "jmp" => Ok(Opcode::Jump),
+            "jz" => Ok(Opcode::JumpIfZero),
+            "jnz" => Ok(Opcode::JumpIfNotZero),
+            "jgz" => Ok(Opcode::JumpIfPositive),
+            "jlz" => Ok(Opcode::JumpIfNegative),
+            _ => Err(InstructionErr::UnknownInstruction(s.to_string()))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    NoOp,
+    Add(String, Operand),
+    Sub(String, Operand),
+    Mul(String, Operand),
+    Mov(String, Operand),
+    // Jumps name their target label, which `Program` checks exists
+    Jump(Condition, String)
+}
+
+impl Instruction {
+    pub fn opcode(&self) -> Opcode {
+        match self {
+            Instruction::NoOp => Opcode::NoOp,
+            Instruction::Add(..) => Opcode::Add,
+            Instruction::Sub(..) => Opcode::Sub,
+            Instruction::Mul(..) => Opcode::Mul,
+            Instruction::Mov(..) => Opcode::Mov,
+            Instruction::Jump(Condition::Always, _) => Opcode::Jump,
+            Instruction::Jump(Condition::Zero(_), _) => Opcode::JumpIfZero,
+            Instruction::Jump(Condition::NotZero(_), _) => Opcode::JumpIfNotZero,
+            Instruction::Jump(Condition::Positive(_), _) => Opcode::JumpIfPositive,
+            Instruction::Jump(Condition::Negative(_), _) => Opcode::JumpIfNegative
+        }
+    }
+}
+
+// Accepts the puzzle's `noop` and `addx 5`, plus `addx a 5`, `subx a b`, `mulx 3`, `mov a x`,
+// `jmp label` and `jz a label` (likewise `jnz`, `jgz` and `jlz`). Operands may be separated by
+// commas as well as spaces. Arithmetic without a destination works on register `x`.
+impl FromStr for Instruction {
+    type Err = InstructionErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut split_iter = s.split(|c: char| c.is_whitespace() || c == ',').filter(|part| !part.is_empty());
+        let opcode = Opcode::from_str(split_iter.next().ok_or(InstructionErr::MissingOperand)?)?;
+        let operands = split_iter.collect::<Vec<_>>();
+
+        let expect_count = |count: usize| match operands.len().cmp(&count) {
+            std::cmp::Ordering::Less => Err(InstructionErr::MissingOperand),
+            std::cmp::Ordering::Greater => Err(InstructionErr::TrailingOperand(operands[count].to_string())),
+            std::cmp::Ordering::Equal => Ok(())
+        };
+        let arithmetic = |build: fn(String, Operand) -> Instruction| match operands.as_slice() {
+            [value] => Ok(build(DEFAULT_REGISTER.to_string(), Operand::from_str(value)?)),
+            _ => {
+                expect_count(2)?;
+                Ok(build(parse_register(operands[0])?, Operand::from_str(operands[1])?))
+            }
+        };
+        let jump = |build: fn(String) -> Condition| {
+            expect_count(2)?;
+            Ok(Instruction::Jump(build(parse_register(operands[0])?), parse_label(operands[1])?))
+        };
+
+        match opcode {
+            Opcode::NoOp => expect_count(0).map(|_| Instruction::NoOp),
+            Opcode::Add => arithmetic(Instruction::Add),
+            Opcode::Sub => arithmetic(Instruction::Sub),
+            Opcode::Mul => arithmetic(Instruction::Mul),
+            Opcode::Mov => {
+                expect_count(2)?;
+                Ok(Instruction::Mov(parse_register(operands[0])?, Operand::from_str(operands[1])?))
+            }
+            Opcode::Jump => {
+                expect_count(1)?;
+                Ok(Instruction::Jump(Condition::Always, parse_label(operands[0])?))
+            }
+            Opcode::JumpIfZero => jump(Condition::Zero),
+            Opcode::JumpIfNotZero => jump(Condition::NotZero),
+            Opcode::JumpIfPositive => jump(Condition::Positive),
+            Opcode::JumpIfNegative => jump(Condition::Negative)
+        }
+    }
+}
+
+// Register names are lowercase words, so they can't be mistaken for numbers.
+fn parse_register(s: &str) -> Result<String, InstructionErr> {
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_lowercase()) {
+        Ok(s.to_string())
+    }
+    else {
+        Err(InstructionErr::InvalidOperand(s.to_string()))
+    }
+}
+
+pub fn parse_label(s: &str) -> Result<String, InstructionErr> {
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(s.to_string())
+    }
+    else {
+        Err(InstructionErr::InvalidOperand(s.to_string()))
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Register(register) => write!(f, "{}", register),
+            Operand::Value(value) => write!(f, "{}", value)
+        }
+    }
+}
+
+// Writes instructions back out in the form `from_str` reads, always naming the register.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::NoOp => write!(f, "noop"),
+            Instruction::Add(register, operand) => write!(f, "addx {} {}", register, operand),
+            Instruction::Sub(register, operand) => write!(f, "subx {} {}", register, operand),
+            Instruction::Mul(register, operand) => write!(f, "mulx {} {}", register, operand),
+            Instruction::Mov(register, operand) => write!(f, "mov {} {}", register, operand),
+            Instruction::Jump(Condition::Always, label) => write!(f, "jmp {}", label),
+            Instruction::Jump(condition, label) => {
+                let mnemonic = match condition {
+                    Condition::Zero(_) => "jz",
+                    Condition::NotZero(_) => "jnz",
+                    Condition::Positive(_) => "jgz",
+                    _ => "jlz"
+                };
+                write!(f, "{} {} {}", mnemonic, condition.register().unwrap(), label)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Instruction, InstructionErr> {
+        Instruction::from_str(s)
+    }
+
+    fn register(name: &str) -> Operand {
+        Operand::Register(name.to_string())
+    }
+
+    #[test]
+    fn parses_every_opcode() {
+        let x = || DEFAULT_REGISTER.to_string();
+        let a = || "a".to_string();
+        let cases = [
+            ("noop", Instruction::NoOp),
+            ("addx 5", Instruction::Add(x(), Operand::Value(5))),
+            ("addx a -5", Instruction::Add(a(), Operand::Value(-5))),
+            ("subx a, b", Instruction::Sub(a(), register("b"))),
+            ("mulx 3", Instruction::Mul(x(), Operand::Value(3))),
+            ("mulx x,a", Instruction::Mul(x(), register("a"))),
+            ("mov a x", Instruction::Mov(a(), register("x"))),
+            ("mov a,  -2", Instruction::Mov(a(), Operand::Value(-2))),
+            ("jmp top", Instruction::Jump(Condition::Always, "top".to_string())),
+            ("jz a top", Instruction::Jump(Condition::Zero(a()), "top".to_string())),
+            ("jnz a, top", Instruction::Jump(Condition::NotZero(a()), "top".to_string())),
+            ("jgz x end_1", Instruction::Jump(Condition::Positive(x()), "end_1".to_string())),
+            ("jlz a,end", Instruction::Jump(Condition::Negative(a()), "end".to_string()))
+        ];
+        for (source, expected) in cases {
+            let instruction = parse(source).unwrap();
+            assert_eq!(instruction, expected, "{}", source);
+            // Writing it back out always names the register, and reads back the same
+            assert_eq!(parse(&instruction.to_string()).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_instructions() {
+        assert_eq!(parse("addy 5"), Err(InstructionErr::UnknownInstruction("addy".to_string())));
+        assert_eq!(parse(""), Err(InstructionErr::MissingOperand));
+        assert_eq!(parse("addx"), Err(InstructionErr::MissingOperand));
+        assert_eq!(parse("noop 1"), Err(InstructionErr::TrailingOperand("1".to_string())));
+        assert_eq!(parse("addx a 1 2"), Err(InstructionErr::TrailingOperand("2".to_string())));
+        assert_eq!(parse("mov 1 a"), Err(InstructionErr::InvalidOperand("1".to_string())));
+        assert_eq!(parse("addx A"), Err(InstructionErr::InvalidOperand("A".to_string())));
+        assert_eq!(parse("jmp top!"), Err(InstructionErr::InvalidOperand("top!".to_string())));
+    }
+}
@@ -1,123 +1,81 @@
-use std::str::FromStr;
+mod cpu;
+mod crt;
+mod debugger;
+mod instruction;
+mod observer;
+mod ocr;
+mod program;
+
+use std::{env, fs, io, str::FromStr};
+
+use cpu::{CycleCosts, Cpu};
+use crt::Crt;
+use debugger::Debugger;
+use instruction::Opcode;
+use observer::SignalSampler;
+use ocr::Font;
+use program::Program;
+
+// Stops programs that loop forever unless --max-cycles= says otherwise
+const DEFAULT_CYCLE_LIMIT: usize = 1_000_000;
 
-#[derive(Debug, Clone, Copy)]
-struct GenericParseErr;
-
-#[derive(Clone, Copy)]
-enum Instruction {
-    NoOp,
-    Add(i32)
-}
-
-impl FromStr for Instruction {
-    type Err = GenericParseErr;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split_iter = s.split(" ");
-        let instruction = split_iter.next().unwrap();
-        match instruction {
-            "noop" => Ok(Instruction::NoOp),
-            "addx" => {
-                let add_by = i32::from_str(split_iter.next().unwrap()).unwrap();
-                Ok(Instruction::Add(add_by))
-            },
-            _ => Err(GenericParseErr)
-        }
-    }
-}
-
-struct CPU {
-    register: i32,
-    current_instruction: Option<Instruction>,
-    remaining_cycles_for_current_instruction: i32,
-    current_cycle: usize
-}
-
-impl Default for CPU {
-    fn default() -> Self {
-        CPU {
-            register: 1,
-            current_instruction: None,
-            remaining_cycles_for_current_instruction: 0,
-            current_cycle: 0
-        }
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let flag_value = |name: &str| args.iter().find_map(|arg| arg.strip_prefix(name));
+    let source = match flag_value("--program=") {
+        Some(path) => fs::read_to_string(path).unwrap(),
+        None => include_str!("input.txt").to_string()
+    };
+    let program = match Program::from_str(&source) {
+        Ok(program) => program,
+        Err(err) => panic!("{:?}", err)
+    };
+
+    // --cost=mulx:4 overrides how many cycles an instruction takes
+    let cycle_costs = args.iter()
+        .filter_map(|arg| arg.strip_prefix("--cost=")?.split_once(':'))
+        .fold(CycleCosts::default(), |costs, (opcode, cycles)| {
+            costs.with_cost(Opcode::from_str(opcode).unwrap(), cycles.parse().expect("--cost cycles must be a number"))
+        });
+    let cycle_limit = flag_value("--max-cycles=").map_or(DEFAULT_CYCLE_LIMIT, |limit| limit.parse().expect("--max-cycles must be a number"));
+    let mut cpu = Cpu::default().with_cycle_costs(cycle_costs).with_cycle_limit(cycle_limit);
+
+    if args.iter().any(|arg| arg == "--debug") {
+        let mut debugger = Debugger::new(cpu, &program);
+        return debugger.repl(io::stdin().lock(), io::stdout()).unwrap();
     }
-}
 
-fn cycles_for_instruction(instruction: &Instruction) -> i32 {
-    match instruction {
-        Instruction::NoOp => 1,
-        Instruction::Add(_) => 2
+    let mut sampler = SignalSampler::new(vec![20, 60, 100, 140, 180, 220]);
+    let flag_number = |name: &str, default: usize| flag_value(name).map_or(default, |n| n.parse().expect("CRT sizes must be numbers"));
+    let mut crt = Crt::new(flag_number("--crt-width=", 40), flag_number("--crt-height=", 6), flag_number("--sprite-width=", 3));
+    cpu.run(&program, &mut [&mut sampler, &mut crt]);
+    if !cpu.finished(&program) {
+        eprintln!("Stopped after {} cycles without finishing, use --max-cycles= to allow more", cpu.current_cycle);
     }
-}
-
-impl CPU {
-    fn process_instructions<I>(&mut self, mut instruction_iter: I, cycles_of_interest: &Vec<usize>) -> Vec<i32> 
-    where I: Iterator<Item = Instruction> {
-        let mut result = vec![];
-        self.current_cycle = 0;
-        self.remaining_cycles_for_current_instruction = 0;
-        self.register = 1;
-
-        loop {
-            self.current_cycle += 1;
-
-            //First, we want to fetch the next instruction if we do not have one we're currently executing
-            if self.current_instruction.is_none() {
-                self.current_instruction = instruction_iter.next();
-                match &self.current_instruction {
-                    Some(instruction) => { self.remaining_cycles_for_current_instruction = cycles_for_instruction(instruction) },
-                    None => break
-                }
-            }
-
-            //Added for observing the signal strength values outlined in the problem
-            if cycles_of_interest.contains(&self.current_cycle) {
-                result.push(self.register);
-            }            
-
-            self.remaining_cycles_for_current_instruction -= 1;
-            if self.remaining_cycles_for_current_instruction == 0 {
-                match self.current_instruction.unwrap() {
-                    Instruction::NoOp => (),
-                    Instruction::Add(val) => self.register += val                    
-                }
-                self.current_instruction = None;
-            }
-        }
-
-        result
-    }
-}
-
-fn main() {
-    let instructions = include_str!("input.txt").lines().map(|l| Instruction::from_str(l).unwrap()).collect::<Vec<_>>();
-
-    let mut cpu = CPU::default();
 
     //Part 1;
-    let intervals = vec![20, 60, 100, 140, 180, 220];
-    let values = cpu.process_instructions(instructions.clone().into_iter(), &intervals);
-
-    let signal_strength_sum: i32 = intervals.iter().zip(values.iter()).map(|(i, v)| (*i as i32) * (*v)).sum();
-    println!("The signal strength is {}", signal_strength_sum);
+    println!("The signal strength is {}", sampler.total());
 
     //Part 2 
-    let crt_intervals = (1..241).collect::<Vec<_>>();
-    let register_values = cpu.process_instructions(instructions.clone().into_iter(), &crt_intervals);
-    println!("{}", register_values.len());
-    let image = crt_intervals.iter().zip(register_values.iter()).map(|(i, v)| { 
-        let difference = *v - ((*i - 1) % 40) as i32;
-        if i32::abs(difference) <= 1 {
-            return '#'
-        }
-        '.'
-    });
+    let screen = crt.screen();
+    println!("{}", screen.to_text());
+    if let Some(path) = flag_value("--pbm=") {
+        fs::write(path, screen.to_pbm()).unwrap();
+    }
+
+    // --glyph=Y:#...#.#.#..#...#...#.. teaches the reader another letter
+    let font = args.iter()
+        .filter_map(|arg| arg.strip_prefix("--glyph=")?.split_once(':'))
+        .fold(Font::default(), |font, (letter, pattern)| {
+            font.with_glyph(letter.chars().next().unwrap(), pattern).expect("--glyph pattern must be 4x6 of # and .")
+        });
+    let recognition = font.read(&screen.to_text());
+    println!("The CRT reads {}", recognition.text);
+    for glyph in recognition.unknown {
+        println!("Unknown glyph {} at column {}: {}", glyph.index, glyph.column, glyph.pattern);
+    }
 
-    for (pixel_idx, pixel) in image.enumerate() {
-        if pixel_idx != 0 && pixel_idx % 40 == 0 {
-            print!("\n");
-        }
-        print!("{}", pixel);
+    if flag_value("--program=").is_some() {
+        println!("Finished on cycle {} with registers {:?}", cpu.current_cycle, cpu.registers);
     }
 }
@@ -0,0 +1,34 @@
+use crate::{cpu::Cpu, instruction::DEFAULT_REGISTER};
+
+// Called part way through every cycle, after the instruction has been fetched and before it takes
+// effect, so `cpu` holds the register values "during" the cycle as the puzzle describes them.
+pub trait CycleObserver {
+    fn observe(&mut self, cpu: &Cpu);
+}
+
+// Records the signal strength (cycle number times `x`) on each of the given cycles.
+pub struct SignalSampler {
+    cycles: Vec<usize>,
+    pub strengths: Vec<i32>
+}
+
+impl SignalSampler {
+    pub fn new(mut cycles: Vec<usize>) -> Self {
+        cycles.sort_unstable();
+        cycles.dedup();
+        SignalSampler { cycles, strengths: vec![] }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.strengths.iter().sum()
+    }
+}
+
+impl CycleObserver for SignalSampler {
+    // Cycles only ever go up, so the next one of interest is always the first not yet sampled.
+    fn observe(&mut self, cpu: &Cpu) {
+        if self.cycles.get(self.strengths.len()) == Some(&cpu.current_cycle) {
+            self.strengths.push(cpu.current_cycle as i32 * cpu.register(DEFAULT_REGISTER));
+        }
+    }
+}
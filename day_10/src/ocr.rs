@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrErr {
+    // The pattern didn't have exactly one `#` or `.` per pixel of a glyph
+    BadGlyph(char)
+}
+
+// A glyph that isn't in the font, by its index along the line and the column it starts at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownGlyph {
+    pub index: usize,
+    pub column: usize,
+    pub pattern: String
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recognition {
+    // Unknown glyphs show up as `?`
+    pub text: String,
+    pub unknown: Vec<UnknownGlyph>
+}
+
+// Block letters of a fixed size, laid out left to right with a blank column between each.
+#[derive(Debug, Clone)]
+pub struct Font {
+    glyph_width: usize,
+    glyph_height: usize,
+    glyphs: HashMap<String, char>
+}
+
+// The letters the puzzles are known to use, 4 pixels wide and 6 high.
+const BLOCK_LETTERS: [(char, &str); 17] = [
+    ('A', ".##. #..# #..# #### #..# #..#"),
+    ('B', "###. #..# ###. #..# #..# ###."),
+    ('C', ".##. #..# #... #... #..# .##."),
+    ('E', "#### #... ###. #... #... ####"),
+    ('F', "#### #... ###. #... #... #..."),
+    ('G', ".##. #..# #... #.## #..# .###"),
+    ('H', "#..# #..# #### #..# #..# #..#"),
+    ('I', ".### ..#. ..#. ..#. ..#. .###"),
+    ('J', "..## ...# ...# ...# #..# .##."),
+    ('K', "#..# #.#. ##.. #.#. #.#. #..#"),
+    ('L', "#... #... #... #... #... ####"),
+    ('O', ".##. #..# #..# #..# #..# .##."),
+    ('P', "###. #..# #..# ###. #... #..."),
+    ('R', "###. #..# #..# ###. #.#. #..#"),
+    ('S', ".### #... #... .##. ...# ###."),
+    ('U', "#..# #..# #..# #..# #..# .##."),
+    ('Z', "#### ...# ..#. .#.. #... ####")
+];
+
+impl Default for Font {
+    fn default() -> Self {
+        BLOCK_LETTERS.iter().fold(Font::new(4, 6), |font, (letter, pattern)| font.with_glyph(*letter, pattern).unwrap())
+    }
+}
+
+impl Font {
+    pub fn new(glyph_width: usize, glyph_height: usize) -> Self {
+        Font { glyph_width, glyph_height, glyphs: HashMap::new() }
+    }
+
+    // Adds or replaces a glyph. The pattern is its rows from top to bottom, with any whitespace
+    // between them ignored.
+    pub fn with_glyph(mut self, letter: char, pattern: &str) -> Result<Self, OcrErr> {
+        let pattern = pattern.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+        if pattern.len() != self.glyph_width * self.glyph_height || pattern.chars().any(|c| c != '#' && c != '.') {
+            return Err(OcrErr::BadGlyph(letter));
+        }
+        self.glyphs.insert(pattern, letter);
+        Ok(self)
+    }
+
+    // Reads a line of text from rows of `#` and `.`, one row per line. Columns past the right
+    // edge count as unlit, and a glyph with no lit pixels reads as a space.
+    pub fn read(&self, image: &str) -> Recognition {
+        let rows = image.lines().take(self.glyph_height).map(|row| row.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let pixel = |x: usize, y: usize| rows.get(y).and_then(|row| row.get(x)).copied().unwrap_or('.');
+
+        let mut recognition = Recognition { text: String::new(), unknown: vec![] };
+        for (index, column) in (0..width).step_by(self.glyph_width + 1).enumerate() {
+            let pattern = (0..self.glyph_height)
+                .flat_map(|y| (column..column + self.glyph_width).map(move |x| (x, y)))
+                .map(|(x, y)| if pixel(x, y) == '#' { '#' } else { '.' })
+                .collect::<String>();
+
+            match self.glyphs.get(&pattern) {
+                Some(letter) => recognition.text.push(*letter),
+                None if !pattern.contains('#') => recognition.text.push(' '),
+                None => {
+                    recognition.text.push('?');
+                    recognition.unknown.push(UnknownGlyph { index, column, pattern });
+                }
+            }
+        }
+        recognition
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{cpu::Cpu, crt::Crt, program::Program};
+
+    // Lays 4x6 glyph patterns out side by side with a blank column after each.
+    fn image(patterns: &[&str]) -> String {
+        let rows = patterns.iter().map(|pattern| pattern.split_whitespace().collect::<Vec<_>>()).collect::<Vec<_>>();
+        (0..6).map(|y| rows.iter().map(|glyph| format!("{}.", glyph[y])).collect::<String>() + "\n").collect()
+    }
+
+    fn letter(letter: char) -> &'static str {
+        BLOCK_LETTERS.iter().find(|(c, _)| *c == letter).unwrap().1
+    }
+
+    #[test]
+    fn reads_the_default_screen() {
+        let program = Program::from_str(include_str!("input.txt")).unwrap();
+        let mut crt = Crt::default();
+        Cpu::default().run(&program, &mut [&mut crt]);
+
+        let screen = crt.screen().to_text();
+        assert_eq!(screen.lines().map(|row| row.len()).collect::<Vec<_>>(), vec![40; 6]);
+        assert_eq!(Font::default().read(&screen), Recognition { text: "PLULKBZH".to_string(), unknown: vec![] });
+    }
+
+    #[test]
+    fn reports_unknown_glyphs_by_index_and_column() {
+        let blank = "....  ....  ....  ....  ....  ....";
+        let smiley = ".... #..# .... #..# .##. ....";
+        let recognition = Font::default().read(&image(&[letter('H'), letter('I'), smiley, blank, letter('O')]));
+        assert_eq!(recognition.text, "HI? O");
+        assert_eq!(recognition.unknown, vec![UnknownGlyph {
+            index: 2,
+            column: 10,
+            pattern: smiley.split_whitespace().collect()
+        }]);
+    }
+
+    #[test]
+    fn with_glyph_teaches_new_letters() {
+        let y = "#..# #..# .##. ..#. ..#. ..#.";
+        let screen = image(&[letter('H'), y]);
+        assert_eq!(Font::default().read(&screen).text, "H?");
+        assert_eq!(Font::default().with_glyph('Y', y).unwrap().read(&screen).text, "HY");
+
+        // Patterns are only checked for size and characters, not for whitespace between rows
+        assert!(Font::default().with_glyph('Y', &y.replace(' ', "")).is_ok());
+        assert_eq!(Font::default().with_glyph('Y', "#..# #..# .##. ..#. ..#.").unwrap_err(), OcrErr::BadGlyph('Y'));
+        assert_eq!(Font::default().with_glyph('Y', "#..# #..# .##. ..#. ..#. ..#..").unwrap_err(), OcrErr::BadGlyph('Y'));
+        assert_eq!(Font::default().with_glyph('Y', "#..# #..# .##. ..#. ..#. ..X.").unwrap_err(), OcrErr::BadGlyph('Y'));
+    }
+}
@@ -0,0 +1,100 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::instruction::{self, Instruction, InstructionErr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgramErr {
+    Instruction { line_number: usize, err: InstructionErr },
+    DuplicateLabel { line_number: usize, label: String },
+    UnknownLabel { line_number: usize, label: String }
+}
+
+// A parsed program with its labels resolved to instruction indices.
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    // The source line each instruction came from, 1-based, for error messages and the debugger
+    pub line_numbers: Vec<usize>,
+    labels: HashMap<String, usize>
+}
+
+impl Program {
+    // Where execution continues after jumping to `label`.
+    pub fn label_target(&self, label: &str) -> usize {
+        self.labels[label]
+    }
+}
+
+// One instruction or `label:` per line. Blank lines and anything after a `;` are ignored. A label
+// marks the instruction after it, or the end of the program if there isn't one.
+impl FromStr for Program {
+    type Err = ProgramErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut program = Program::default();
+        for (line_idx, line) in s.lines().enumerate() {
+            let line_number = line_idx + 1;
+            let code = line.split(';').next().unwrap().trim();
+            if code.is_empty() {
+                continue;
+            }
+
+            if let Some(label) = code.strip_suffix(':') {
+                let label = instruction::parse_label(label.trim())
+                    .map_err(|err| ProgramErr::Instruction { line_number, err })?;
+                if program.labels.contains_key(&label) {
+                    return Err(ProgramErr::DuplicateLabel { line_number, label });
+                }
+                program.labels.insert(label, program.instructions.len());
+                continue;
+            }
+
+            let instruction = Instruction::from_str(code).map_err(|err| ProgramErr::Instruction { line_number, err })?;
+            program.instructions.push(instruction);
+            program.line_numbers.push(line_number);
+        }
+
+        for (instruction, line_number) in program.instructions.iter().zip(&program.line_numbers) {
+            if let Instruction::Jump(_, label) = instruction {
+                if !program.labels.contains_key(label) {
+                    return Err(ProgramErr::UnknownLabel { line_number: *line_number, label: label.clone() });
+                }
+            }
+        }
+        Ok(program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::{Condition, Operand};
+
+    #[test]
+    fn resolves_labels_and_skips_comments() {
+        let program = Program::from_str("; count down\n\nstart:\n  mov a 2 ; two passes\nloop:\nsubx a 1\njnz a loop\nend:\n").unwrap();
+        assert_eq!(program.instructions, vec![
+            Instruction::Mov("a".to_string(), Operand::Value(2)),
+            Instruction::Sub("a".to_string(), Operand::Value(1)),
+            Instruction::Jump(Condition::NotZero("a".to_string()), "loop".to_string())
+        ]);
+        assert_eq!(program.line_numbers, vec![4, 6, 7]);
+        assert_eq!((program.label_target("start"), program.label_target("loop"), program.label_target("end")), (0, 1, 3));
+    }
+
+    #[test]
+    fn reports_label_errors_with_their_line() {
+        assert_eq!(
+            Program::from_str("top:\nnoop\ntop:\njmp top").unwrap_err(),
+            ProgramErr::DuplicateLabel { line_number: 3, label: "top".to_string() }
+        );
+        assert_eq!(
+            Program::from_str("noop\njz x nowhere").unwrap_err(),
+            ProgramErr::UnknownLabel { line_number: 2, label: "nowhere".to_string() }
+        );
+        assert_eq!(
+            Program::from_str("noop\naddx").unwrap_err(),
+            ProgramErr::Instruction { line_number: 2, err: InstructionErr::MissingOperand }
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::str::FromStr;
+
+use crate::section::{Section, SectionErr};
+
+#[derive(Debug, Clone, Copy)]
+pub struct GroupErr(pub SectionErr);
+
+impl From<SectionErr> for GroupErr {
+    fn from(err: SectionErr) -> Self {
+        GroupErr(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub members: Vec<Section>
+}
+
+impl FromStr for Group {
+    type Err = GroupErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let members = s.split(',')
+            .map(Section::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Group { members })
+    }
+}
+
+impl Group {
+    pub fn has_overlap(&self) -> bool {
+        !self.containment_graph().is_empty()
+    }
+
+    pub fn has_intersection(&self) -> bool {
+        self.member_pairs().any(|(i, j)| {
+            let (first, second) = (self.members[i], self.members[j]);
+            first.intersects_other(&second) || second.intersects_other(&first)
+        })
+    }
+
+    // Every (container, contained) pair of member indices. Identical sections contain each other,
+    // so they show up in both directions.
+    pub fn containment_graph(&self) -> Vec<(usize, usize)> {
+        let mut edges = vec![];
+        for (i, container) in self.members.iter().enumerate() {
+            for (j, contained) in self.members.iter().enumerate() {
+                if i != j && container.overlaps_other(contained) {
+                    edges.push((i, j));
+                }
+            }
+        }
+        edges
+    }
+
+    // The largest number of members assigned to any single section.
+    pub fn max_overlap_depth(&self) -> usize {
+        let mut events = self.members.iter()
+            .flat_map(|member| [(member.start as u64, 1i64), (member.end as u64 + 1, -1i64)])
+            .collect::<Vec<_>>();
+        // Ends sort before starts at the same position since -1 < 1.
+        events.sort();
+
+        let mut depth = 0i64;
+        let mut max_depth = 0i64;
+        for (_, delta) in events {
+            depth += delta;
+            max_depth = max_depth.max(depth);
+        }
+        max_depth as usize
+    }
+
+    // Indices of members whose sections are entirely covered by the union of the other members.
+    pub fn redundant_members(&self) -> Vec<usize> {
+        (0..self.members.len())
+            .filter(|&i| {
+                let others = self.members.iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, section)| *section)
+                    .collect::<Vec<_>>();
+                let member = self.members[i];
+                merge(others).iter().any(|covered| covered.overlaps_other(&member))
+            })
+            .collect()
+    }
+
+    fn member_pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.members.len()).flat_map(move |i| (i + 1..self.members.len()).map(move |j| (i, j)))
+    }
+}
+
+// Sections are whole numbers, so 1-3 and 4-6 together cover 1-6.
+fn merge(mut sections: Vec<Section>) -> Vec<Section> {
+    sections.sort_by_key(|section| (section.start, section.end));
+    sections.into_iter().fold(vec![], |mut merged: Vec<Section>, current| {
+        match merged.last_mut() {
+            Some(last) if current.start <= last.end.saturating_add(1) => last.end = last.end.max(current.end),
+            _ => merged.push(current)
+        }
+        merged
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(s: &str) -> Group {
+        Group::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn identical_sections_contain_each_other() {
+        let identical = group("2-5,2-5");
+        assert_eq!(identical.containment_graph(), vec![(0, 1), (1, 0)]);
+        assert!(identical.has_overlap());
+        assert_eq!(identical.max_overlap_depth(), 2);
+        assert_eq!(identical.redundant_members(), vec![0, 1]);
+
+        let nested = group("1-9,3-4,3-4");
+        assert_eq!(nested.containment_graph(), vec![(0, 1), (0, 2), (1, 2), (2, 1)]);
+        assert_eq!(nested.max_overlap_depth(), 3);
+    }
+
+    #[test]
+    fn max_overlap_depth_counts_shared_endpoints() {
+        assert_eq!(group("1-3,3-5").max_overlap_depth(), 2);
+        assert_eq!(group("1-3,4-5").max_overlap_depth(), 1);
+        assert_eq!(group("1-10,2-3,5-6,6-8").max_overlap_depth(), 3);
+        assert_eq!(group("5-4294967295,4294967295-4294967295").max_overlap_depth(), 2);
+    }
+
+    #[test]
+    fn adjacent_sections_merge_when_checking_redundancy() {
+        // 1-3 and 4-6 leave no gap, so together they cover 1-6
+        assert_eq!(group("1-6,1-3,4-6").redundant_members(), vec![0, 1, 2]);
+        assert_eq!(group("2-6,1-3,4-7").redundant_members(), vec![0]);
+        assert!(group("2-6,1-3,4-7").containment_graph().is_empty());
+        // 3 is missing, so 1-6 is still needed
+        assert_eq!(group("1-6,1-2,4-6").redundant_members(), vec![1, 2]);
+    }
+
+    #[test]
+    fn single_members_are_never_redundant() {
+        let single = group("3-9");
+        assert!(single.containment_graph().is_empty());
+        assert!(!single.has_overlap());
+        assert!(!single.has_intersection());
+        assert_eq!(single.max_overlap_depth(), 1);
+        assert!(single.redundant_members().is_empty());
+    }
+}
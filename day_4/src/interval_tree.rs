@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+
+use crate::{group::Group, section::Section};
+
+// An AVL tree keyed on (start, end), where every node also tracks the largest end in its subtree.
+// That lets the queries skip whole subtrees that finish before the range of interest, so
+// `intersecting` visits O(log n + k log n) nodes for k results. `containing` has the same bound:
+// only the search path for `range.start` mixes starts on both sides of it, and every other subtree
+// it enters starts early enough throughout and has `max_end >= range.end`, so holds a match.
+// Sections assigned more than once share a node.
+#[derive(Debug)]
+struct Node<T> {
+    section: Section,
+    values: Vec<T>,
+    max_end: u32,
+    height: i32,
+    left: Option<Box<Node<T>>>,
+    right: Option<Box<Node<T>>>
+}
+
+#[derive(Debug)]
+pub struct IntervalTree<T> {
+    root: Option<Box<Node<T>>>,
+    len: usize
+}
+
+impl<T> Default for IntervalTree<T> {
+    fn default() -> Self {
+        IntervalTree { root: None, len: 0 }
+    }
+}
+
+impl IntervalTree<(usize, usize)> {
+    // Indexes every member of every group by (group index, member index).
+    pub fn from_groups(groups: &[Group]) -> Self {
+        let mut tree = IntervalTree::default();
+        for (group_idx, group) in groups.iter().enumerate() {
+            for (member_idx, section) in group.members.iter().enumerate() {
+                tree.insert(*section, (group_idx, member_idx));
+            }
+        }
+        tree
+    }
+}
+
+impl<T: PartialEq> IntervalTree<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn insert(&mut self, section: Section, value: T) {
+        self.root = Some(insert(self.root.take(), section, value));
+        self.len += 1;
+    }
+
+    // Removes one entry equal to `value` stored under `section`, returning whether one was found.
+    pub fn remove(&mut self, section: Section, value: &T) -> bool {
+        let (root, removed) = remove(self.root.take(), section, value);
+        self.root = root;
+        if removed {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    // Everything assigned to the given section number.
+    pub fn at_point(&self, point: u32) -> Vec<(Section, &T)> {
+        self.intersecting(Section { start: point, end: point })
+    }
+
+    // Everything that shares at least one section number with `range`.
+    pub fn intersecting(&self, range: Section) -> Vec<(Section, &T)> {
+        let mut results = vec![];
+        collect_intersecting(&self.root, range, &mut results);
+        results
+    }
+
+    // Everything whose assignment fully contains `range`.
+    pub fn containing(&self, range: Section) -> Vec<(Section, &T)> {
+        let mut results = vec![];
+        collect_containing(&self.root, range, &mut results);
+        results
+    }
+}
+
+fn key(section: &Section) -> (u32, u32) {
+    (section.start, section.end)
+}
+
+fn height<T>(node: &Option<Box<Node<T>>>) -> i32 {
+    node.as_ref().map_or(0, |n| n.height)
+}
+
+fn max_end<T>(node: &Option<Box<Node<T>>>) -> u32 {
+    node.as_ref().map_or(0, |n| n.max_end)
+}
+
+fn update<T>(node: &mut Box<Node<T>>) {
+    node.height = 1 + height(&node.left).max(height(&node.right));
+    node.max_end = node.section.end.max(max_end(&node.left)).max(max_end(&node.right));
+}
+
+fn rotate_right<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    let mut pivot = node.left.take().unwrap();
+    node.left = pivot.right.take();
+    update(&mut node);
+    pivot.right = Some(node);
+    update(&mut pivot);
+    pivot
+}
+
+fn rotate_left<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    let mut pivot = node.right.take().unwrap();
+    node.right = pivot.left.take();
+    update(&mut node);
+    pivot.left = Some(node);
+    update(&mut pivot);
+    pivot
+}
+
+fn rebalance<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    update(&mut node);
+    let balance = height(&node.left) - height(&node.right);
+    if balance > 1 {
+        let left = node.left.take().unwrap();
+        node.left = Some(if height(&left.left) < height(&left.right) { rotate_left(left) } else { left });
+        rotate_right(node)
+    }
+    else if balance < -1 {
+        let right = node.right.take().unwrap();
+        node.right = Some(if height(&right.right) < height(&right.left) { rotate_right(right) } else { right });
+        rotate_left(node)
+    }
+    else {
+        node
+    }
+}
+
+fn insert<T>(node: Option<Box<Node<T>>>, section: Section, value: T) -> Box<Node<T>> {
+    let mut node = match node {
+        Some(n) => n,
+        None => {
+            return Box::new(Node {
+                section,
+                values: vec![value],
+                max_end: section.end,
+                height: 1,
+                left: None,
+                right: None
+            })
+        }
+    };
+
+    match key(&section).cmp(&key(&node.section)) {
+        Ordering::Less => node.left = Some(insert(node.left.take(), section, value)),
+        Ordering::Greater => node.right = Some(insert(node.right.take(), section, value)),
+        Ordering::Equal => node.values.push(value)
+    }
+    rebalance(node)
+}
+
+fn remove_min<T>(mut node: Box<Node<T>>) -> (Option<Box<Node<T>>>, Box<Node<T>>) {
+    match node.left.take() {
+        Some(left) => {
+            let (rest, min) = remove_min(left);
+            node.left = rest;
+            (Some(rebalance(node)), min)
+        }
+        None => (node.right.take(), node)
+    }
+}
+
+fn remove<T: PartialEq>(node: Option<Box<Node<T>>>, section: Section, value: &T) -> (Option<Box<Node<T>>>, bool) {
+    let mut node = match node {
+        Some(n) => n,
+        None => return (None, false)
+    };
+
+    let removed = match key(&section).cmp(&key(&node.section)) {
+        Ordering::Less => {
+            let (left, removed) = remove(node.left.take(), section, value);
+            node.left = left;
+            removed
+        }
+        Ordering::Greater => {
+            let (right, removed) = remove(node.right.take(), section, value);
+            node.right = right;
+            removed
+        }
+        Ordering::Equal => {
+            let position = match node.values.iter().position(|v| v == value) {
+                Some(p) => p,
+                None => return (Some(node), false)
+            };
+            node.values.swap_remove(position);
+            if !node.values.is_empty() {
+                return (Some(node), true);
+            }
+
+            match (node.left.take(), node.right.take()) {
+                (None, None) => return (None, true),
+                (Some(child), None) | (None, Some(child)) => return (Some(child), true),
+                (Some(left), Some(right)) => {
+                    let (rest, mut successor) = remove_min(right);
+                    successor.left = Some(left);
+                    successor.right = rest;
+                    node = successor;
+                }
+            }
+            true
+        }
+    };
+    (Some(rebalance(node)), removed)
+}
+
+fn collect_intersecting<'a, T>(node: &'a Option<Box<Node<T>>>, range: Section, results: &mut Vec<(Section, &'a T)>) {
+    let node = match node {
+        Some(n) if n.max_end >= range.start => n,
+        _ => return
+    };
+
+    collect_intersecting(&node.left, range, results);
+    if node.section.start <= range.end {
+        if node.section.end >= range.start {
+            results.extend(node.values.iter().map(|v| (node.section, v)));
+        }
+        collect_intersecting(&node.right, range, results);
+    }
+}
+
+fn collect_containing<'a, T>(node: &'a Option<Box<Node<T>>>, range: Section, results: &mut Vec<(Section, &'a T)>) {
+    let node = match node {
+        Some(n) if n.max_end >= range.end => n,
+        _ => return
+    };
+
+    collect_containing(&node.left, range, results);
+    if node.section.start <= range.start {
+        if node.section.overlaps_other(&range) {
+            results.extend(node.values.iter().map(|v| (node.section, v)));
+        }
+        collect_containing(&node.right, range, results);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift, so the sections are the same on every run
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, bound: u32) -> u32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as u32
+        }
+
+        fn section(&mut self) -> Section {
+            let start = self.below(100);
+            Section { start, end: start + self.below(20) }
+        }
+    }
+
+    // Checks ordering, balance and the cached heights and max ends, returning (height, max_end, len).
+    fn check_node<T>(node: &Option<Box<Node<T>>>, lower: Option<(u32, u32)>, upper: Option<(u32, u32)>) -> (i32, u32, usize) {
+        let node = match node {
+            Some(n) => n,
+            None => return (0, 0, 0)
+        };
+        assert!(!node.values.is_empty());
+        assert!(lower.is_none_or(|lower| lower < key(&node.section)));
+        assert!(upper.is_none_or(|upper| key(&node.section) < upper));
+
+        let (left_height, left_max, left_len) = check_node(&node.left, lower, Some(key(&node.section)));
+        let (right_height, right_max, right_len) = check_node(&node.right, Some(key(&node.section)), upper);
+        assert!((left_height - right_height).abs() <= 1, "unbalanced at {:?}", node.section);
+        assert_eq!(node.height, 1 + left_height.max(right_height));
+        assert_eq!(node.max_end, node.section.end.max(left_max).max(right_max));
+        (node.height, node.max_end, left_len + right_len + node.values.len())
+    }
+
+    fn check_tree(tree: &IntervalTree<usize>) {
+        let (height, _, len) = check_node(&tree.root, None, None);
+        assert_eq!(len, tree.len());
+        // An AVL tree is never more than about 1.44 log2(n) high
+        assert!(height as f64 <= 1.45 * ((len + 2) as f64).log2());
+    }
+
+    fn sorted(results: Vec<(Section, &usize)>) -> Vec<(u32, u32, usize)> {
+        let mut results = results.into_iter().map(|(section, value)| (section.start, section.end, *value)).collect::<Vec<_>>();
+        results.sort_unstable();
+        results
+    }
+
+    fn scan(entries: &[(Section, usize)], keep: impl Fn(&Section) -> bool) -> Vec<(u32, u32, usize)> {
+        let mut results = entries.iter()
+            .filter(|(section, _)| keep(section))
+            .map(|(section, value)| (section.start, section.end, *value))
+            .collect::<Vec<_>>();
+        results.sort_unstable();
+        results
+    }
+
+    #[test]
+    fn stays_balanced_through_inserts_and_removes() {
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        let mut tree = IntervalTree::default();
+        let mut entries = vec![];
+
+        // Ascending inserts are the worst case for an unbalanced tree
+        for value in 0..500 {
+            let section = Section { start: value as u32, end: value as u32 + 1 };
+            tree.insert(section, value);
+            entries.push((section, value));
+            check_tree(&tree);
+        }
+        for value in 500..2000 {
+            let section = rng.section();
+            tree.insert(section, value);
+            entries.push((section, value));
+        }
+        check_tree(&tree);
+
+        while !entries.is_empty() {
+            let (section, value) = entries.swap_remove(rng.below(entries.len() as u32) as usize);
+            assert!(tree.remove(section, &value));
+            assert!(!tree.remove(section, &value));
+            if entries.len() % 50 == 0 {
+                check_tree(&tree);
+            }
+        }
+        assert_eq!(tree.len(), 0);
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn queries_match_a_linear_scan() {
+        let mut rng = Rng(0x9e3779b97f4a7c15);
+        let mut tree = IntervalTree::default();
+        let mut entries = vec![];
+        for value in 0..1000 {
+            let section = rng.section();
+            tree.insert(section, value);
+            entries.push((section, value));
+        }
+        for _ in 0..300 {
+            let (section, value) = entries.swap_remove(rng.below(entries.len() as u32) as usize);
+            tree.remove(section, &value);
+        }
+
+        for point in 0..125 {
+            assert_eq!(sorted(tree.at_point(point)), scan(&entries, |s| s.start <= point && point <= s.end));
+        }
+        for _ in 0..500 {
+            let range = rng.section();
+            assert_eq!(
+                sorted(tree.intersecting(range)),
+                scan(&entries, |s| s.start <= range.end && range.start <= s.end),
+                "intersecting {:?}",
+                range
+            );
+            assert_eq!(
+                sorted(tree.containing(range)),
+                scan(&entries, |s| s.start <= range.start && range.end <= s.end),
+                "containing {:?}",
+                range
+            );
+        }
+    }
+}
@@ -1,87 +1,53 @@
+mod group;
+mod interval_tree;
+mod section;
+
 use std::str::FromStr;
 
+use group::{Group, GroupErr};
+use interval_tree::IntervalTree;
+use section::Section;
+
 fn main() {
     let lines = include_str!("input.txt").lines();
-    let pairs = lines.map(Pair::from_str)
-        .filter_map(|pair| match pair {
-            Ok(p) => Some(p),
-            Err(_) => None
-        })
-        .collect::<Vec<_>>();
-
-    let overlaps = pairs.iter().map(|pair| pair.has_overlap());
-    let sum_overlaps = overlaps.fold(0, |s, o| if o { s + 1 } else { s });
-
-    let intersections = pairs.iter().map(|pair| pair.has_intersection());
-    let sum_intersections = intersections.fold(0, |s, o| if o { s + 1 } else { s });
-
-
-    println!("The number of overlaps is {}, intersections is {}", sum_overlaps, sum_intersections)
-}
-
-#[derive(Clone, Copy)]
-struct Section {
-    start: u32,
-    end: u32
-}
-
-#[derive(Clone, Copy)]
-struct SectionErr;
-
-impl FromStr for Section {
-    type Err = SectionErr;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut iter = s.splitn(2, '-').map(|num| num.parse::<u32>().map_err(|_| SectionErr));
-        let start = iter.next().unwrap();
-        let end = iter.next().unwrap();
-        match (start, end) {
-            (Ok(st), Ok(e)) => Ok(Section{start: st, end: e}),
-            (_, _) => Err(SectionErr)
+    let mut groups = vec![];
+    for (line_idx, line) in lines.enumerate() {
+        match Group::from_str(line) {
+            Ok(group) => groups.push(group),
+            // Reversed ranges would throw the counts below off, so they're left out, but not quietly
+            Err(GroupErr(err)) => eprintln!("Skipping line {}: {:?}", line_idx + 1, err)
         }
     }
-}
 
-impl Section {
-    fn overlaps_other(self, other_section: &Section) -> bool {
-        self.start <= other_section.start && self.end >= other_section.end
-    }
+    let overlaps = groups.iter().map(|group| group.has_overlap());
+    let sum_overlaps = overlaps.fold(0, |s, o| if o { s + 1 } else { s });
 
-    fn intersects_other(self, other_section: &Section) -> bool {
-        (other_section.start <= self.start && self.start <= other_section.end) || (other_section.start <= self.end && self.end <= other_section.end)
-    }
-}
+    let intersections = groups.iter().map(|group| group.has_intersection());
+    let sum_intersections = intersections.fold(0, |s, o| if o { s + 1 } else { s });
 
 
-#[derive(Clone, Copy)]
-struct PairErr;
+    println!("The number of overlaps is {}, intersections is {}", sum_overlaps, sum_intersections);
 
-#[derive(Clone, Copy)]
-struct Pair {
-    first: Section,
-    second: Section
-}
+    let deepest = groups.iter().map(|group| group.max_overlap_depth()).max().unwrap_or(0);
+    let redundant = groups.iter().map(|group| group.redundant_members().len()).sum::<usize>();
+    println!("The deepest overlap is {}, redundant assignments is {}", deepest, redundant);
 
-impl FromStr for Pair {
-    type Err = PairErr;
+    let mut index = IntervalTree::from_groups(&groups);
+    let range = Section { start: 30, end: 40 };
+    println!(
+        "Of {} assignments, {} cover section 57, {} touch {}-{} and {} contain it",
+        index.len(),
+        index.at_point(57).len(),
+        index.intersecting(range).len(),
+        range.start,
+        range.end,
+        index.containing(range).len()
+    );
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut iter = s.splitn(2, ",").map(Section::from_str);
-        let first = iter.next().unwrap();
-        let second = iter.next().unwrap();
-        match (first, second) {
-            (Ok(f), Ok(s)) => Ok(Pair{ first: f, second: s }),
-            (_, _) => Err(PairErr)
+    if let Some(first) = groups.first() {
+        for (member_idx, section) in first.members.iter().enumerate() {
+            index.remove(*section, &(0, member_idx));
         }
+        println!("Without the first group, {} cover section 57", index.at_point(57).len());
     }
 }
-
-impl Pair {
-    fn has_overlap(self) -> bool {
-        self.first.overlaps_other(&self.second) || self.second.overlaps_other(&self.first)
-    }
-
-    fn has_intersection(self) -> bool {
-        self.first.intersects_other(&self.second) || self.second.intersects_other(&self.first)
-    }
-}
\ No newline at end of file
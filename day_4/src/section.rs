@@ -0,0 +1,38 @@
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section {
+    pub start: u32,
+    pub end: u32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionErr {
+    Invalid,
+    // A range written back to front, like `7-3`
+    Reversed { start: u32, end: u32 }
+}
+
+impl FromStr for Section {
+    type Err = SectionErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut iter = s.trim().splitn(2, '-').map(|num| num.parse::<u32>().map_err(|_| SectionErr::Invalid));
+        let start = iter.next().ok_or(SectionErr::Invalid)??;
+        let end = iter.next().ok_or(SectionErr::Invalid)??;
+        if start > end {
+            return Err(SectionErr::Reversed { start, end });
+        }
+        Ok(Section{start, end})
+    }
+}
+
+impl Section {
+    pub fn overlaps_other(self, other_section: &Section) -> bool {
+        self.start <= other_section.start && self.end >= other_section.end
+    }
+
+    pub fn intersects_other(self, other_section: &Section) -> bool {
+        (other_section.start <= self.start && self.start <= other_section.end) || (other_section.start <= self.end && self.end <= other_section.end)
+    }
+}
@@ -0,0 +1,82 @@
+use std::{fmt::Write, fs, io, path::{Path, PathBuf}};
+
+use crate::{file_system::FileSystem, line::Entry};
+
+impl FileSystem {
+    // Walks a real directory, listing it the way a transcript would. Symlinks are skipped.
+    // Subdirectories that can't be read, and ones holding an entry that can't be read or vanished
+    // mid-walk, are left unlisted so their sizes show up as unknown.
+    pub fn from_disk(root: &Path) -> io::Result<FileSystem> {
+        let mut file_system = FileSystem::default();
+        let mut stack: Vec<(PathBuf, usize)> = vec![(root.to_path_buf(), 0)];
+        while let Some((dir_path, index)) = stack.pop() {
+            let dir_entries = match fs::read_dir(&dir_path) {
+                Ok(entries) => entries,
+                Err(err) if index == 0 => return Err(err),
+                Err(_) => continue
+            };
+
+            let mut complete = true;
+            for dir_entry in dir_entries {
+                let read_entry = dir_entry.and_then(|dir_entry| {
+                    let metadata = fs::symlink_metadata(dir_entry.path())?;
+                    Ok((dir_entry, metadata))
+                });
+                let (dir_entry, metadata) = match read_entry {
+                    Ok(read_entry) => read_entry,
+                    Err(_) => {
+                        complete = false;
+                        continue;
+                    }
+                };
+                let name = dir_entry.file_name().to_string_lossy().into_owned();
+                let entry = if metadata.is_dir() {
+                    Entry::Directory(name)
+                }
+                else if metadata.is_file() {
+                    Entry::File(name, metadata.len() as usize)
+                }
+                else {
+                    continue;
+                };
+
+                let is_dir = matches!(entry, Entry::Directory(_));
+                let child_index = file_system.add(index, entry)
+                    .map_err(|err| io::Error::other(format!("{:?}", err)))?;
+                if is_dir {
+                    stack.push((dir_entry.path(), child_index));
+                }
+            }
+            if complete {
+                file_system.mark_listed(index);
+            }
+        }
+        Ok(file_system)
+    }
+
+    // A `$ cd`/`$ ls` transcript that replays back into this tree. Directories that were never
+    // listed get a `cd` but no `ls`.
+    pub fn to_transcript(&self) -> String {
+        let mut output = String::new();
+        let paths = self.paths();
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = self.get_node(index);
+            writeln!(output, "$ cd {}", paths[index]).unwrap();
+            if node.listed {
+                output.push_str("$ ls\n");
+                for child_index in &node.child_node_indices {
+                    match &self.get_node(*child_index).entry {
+                        Entry::Directory(name) => writeln!(output, "dir {}", name).unwrap(),
+                        Entry::File(name, size) => writeln!(output, "{} {}", size, name).unwrap()
+                    }
+                }
+            }
+
+            stack.extend(node.child_node_indices.iter()
+                .rev()
+                .filter(|child_index| matches!(self.get_node(**child_index).entry, Entry::Directory(_))));
+        }
+        output
+    }
+}
@@ -0,0 +1,488 @@
+use std::{cell::OnceCell, collections::HashMap, str::FromStr};
+
+use crate::line::{Command, Entry, Line};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileSystemErr {
+    DirectoryNotFound(String),
+    NotADirectory(String),
+    KindConflict(String),
+    SizeConflict { path: String, existing: usize, listed: usize },
+    NotFound(String),
+    AlreadyExists(String),
+    IsADirectory(String),
+    InvalidName(String),
+    InvalidMove { source: String, destination: String },
+    InvalidLine(String),
+    UnexpectedOutput(String)
+}
+
+// How output lines are read, based on the last command in the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputKind {
+    None,
+    Listing,
+    Ignored
+}
+
+#[derive(Debug)]
+pub struct FileSystem {
+    nodes: Vec<FileSystemNode>,
+    current_index: usize,
+    children_by_name: HashMap<(usize, String), usize>,
+    expected_output: OutputKind,
+    sizes: OnceCell<Vec<usize>>
+}
+
+impl FileSystem {
+    // Adds an entry under `parent_index`, returning its index. Listing the same entry again is a
+    // no-op, but a name that comes back as a different kind or a different size is a conflict.
+    pub fn add(&mut self, parent_index: usize, entry: Entry) -> Result<usize, FileSystemErr> {
+        if let Some(existing) = self.find_child(parent_index, entry.name()) {
+            return match (&existing.entry, &entry) {
+                (Entry::Directory(_), Entry::Directory(_)) => Ok(existing.self_index),
+                (Entry::File(_, existing_size), Entry::File(_, listed_size)) if existing_size == listed_size => Ok(existing.self_index),
+                (Entry::File(_, existing_size), Entry::File(_, listed_size)) => Err(FileSystemErr::SizeConflict {
+                    path: self.path_of(existing.self_index),
+                    existing: *existing_size,
+                    listed: *listed_size
+                }),
+                _ => Err(FileSystemErr::KindConflict(self.path_of(existing.self_index)))
+            };
+        }
+
+        let current_node_count = self.nodes.len();
+        self.children_by_name.insert((parent_index, entry.name().to_string()), current_node_count);
+        let child_node = FileSystemNode {
+            self_index: current_node_count,
+            entry,
+            child_node_indices: vec![],
+            parent_index: Some(parent_index),
+            listed: false
+        };
+
+        self.nodes[parent_index].child_node_indices.push(current_node_count);
+        self.nodes.push(child_node);
+        self.sizes.take();
+        Ok(current_node_count)
+    }
+
+    pub fn get_node(&self, idx: usize) -> &FileSystemNode {
+        &self.nodes[idx]
+    }
+
+    pub fn mark_listed(&mut self, index: usize) {
+        self.nodes[index].listed = true;
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn find_child(&self, parent_index: usize, name: &str) -> Option<&FileSystemNode> {
+        self.children_by_name.get(&(parent_index, name.to_string())).map(|idx| &self.nodes[*idx])
+    }
+
+    pub fn path_of(&self, index: usize) -> String {
+        let mut names = vec![];
+        let mut current = &self.nodes[index];
+        while let Some(parent_index) = current.parent_index {
+            names.push(current.entry.name());
+            current = &self.nodes[parent_index];
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    fn find_dir(&self, parent_index: usize, name: &str, path: &str) -> Result<usize, FileSystemErr> {
+        match self.find_child(parent_index, name) {
+            Some(FileSystemNode { self_index, entry: Entry::Directory(_), .. }) => Ok(*self_index),
+            Some(_) => Err(FileSystemErr::NotADirectory(path.to_string())),
+            None => Err(FileSystemErr::DirectoryNotFound(path.to_string()))
+        }
+    }
+
+    // Resolves a path to a directory index. Paths starting with `/` are absolute, anything else is
+    // relative to the current directory. `..` at the root stays at the root.
+    pub fn resolve(&self, path: &str) -> Result<usize, FileSystemErr> {
+        let start_index = if path.starts_with('/') { 0 } else { self.current_index };
+        path.split('/')
+            .filter(|component| !component.is_empty())
+            .try_fold(start_index, |index, component| match component {
+                "." => Ok(index),
+                ".." => Ok(self.nodes[index].parent_index.unwrap_or(index)),
+                name => self.find_dir(index, name, path)
+            })
+    }
+
+    pub fn cd(&mut self, path: &str) -> Result<usize, FileSystemErr> {
+        self.current_index = self.resolve(path)?;
+        Ok(self.current_index)
+    }
+
+    // Replays a whole transcript, stopping after `until` lines if given. Errors carry the 1-based
+    // line number they happened on.
+    pub fn from_transcript(transcript: &str, until: Option<usize>) -> Result<FileSystem, (usize, FileSystemErr)> {
+        let mut file_system = FileSystem::default();
+        for (line_number, text) in transcript.lines().take(until.unwrap_or(usize::MAX)).enumerate() {
+            Line::from_str(text)
+                .map_err(|_| FileSystemErr::InvalidLine(text.to_string()))
+                .and_then(|line| file_system.replay(line))
+                .map_err(|err| (line_number + 1, err))?;
+        }
+        Ok(file_system)
+    }
+
+    // Applies one line of a terminal transcript. `ls` output is added to the current directory and
+    // `du` output is skipped, since it's derived from the tree rather than describing it.
+    pub fn replay(&mut self, line: Line) -> Result<(), FileSystemErr> {
+        let command = match line {
+            Line::Output(text) => {
+                return match self.expected_output {
+                    OutputKind::Listing => {
+                        let entry = Entry::from_str(&text).map_err(|_| FileSystemErr::InvalidLine(text))?;
+                        self.add(self.current_index, entry).map(|_| ())
+                    }
+                    OutputKind::Ignored => Ok(()),
+                    OutputKind::None => Err(FileSystemErr::UnexpectedOutput(text))
+                };
+            }
+            Line::Command(command) => command
+        };
+
+        self.expected_output = OutputKind::None;
+        match command {
+            Command::ChangeDir(path) => self.cd(&path).map(|_| ()),
+            Command::List => {
+                self.mark_listed(self.current_index);
+                self.expected_output = OutputKind::Listing;
+                Ok(())
+            }
+            Command::MakeDir(path) => self.mkdir(&path).map(|_| ()),
+            Command::Touch(path, size) => self.touch(&path, size).map(|_| ()),
+            Command::Remove { path, recursive } => self.rm(&path, recursive),
+            Command::Move(source, destination) => self.mv(&source, &destination).map(|_| ()),
+            Command::DiskUsage => {
+                self.expected_output = OutputKind::Ignored;
+                Ok(())
+            }
+        }
+    }
+
+    // Splits a path into the directory holding its last component and that component's name.
+    fn split_parent(path: &str) -> Result<(&str, &str), FileSystemErr> {
+        let trimmed = path.trim_end_matches('/');
+        let (parent, name) = match trimmed.rsplit_once('/') {
+            Some(("", name)) => ("/", name),
+            Some((parent, name)) => (parent, name),
+            None => (".", trimmed)
+        };
+        match name {
+            "" | "." | ".." => Err(FileSystemErr::InvalidName(path.to_string())),
+            name => Ok((parent, name))
+        }
+    }
+
+    // Like `resolve`, but the last component may be a file.
+    pub fn resolve_entry(&self, path: &str) -> Result<usize, FileSystemErr> {
+        match FileSystem::split_parent(path) {
+            Ok((parent, name)) => self.find_child(self.resolve(parent)?, name)
+                .map(|node| node.self_index)
+                .ok_or_else(|| FileSystemErr::NotFound(path.to_string())),
+            Err(_) => self.resolve(path)
+        }
+    }
+
+    pub fn mkdir(&mut self, path: &str) -> Result<usize, FileSystemErr> {
+        let (parent, name) = FileSystem::split_parent(path)?;
+        let parent_index = self.resolve(parent)?;
+        if self.find_child(parent_index, name).is_some() {
+            return Err(FileSystemErr::AlreadyExists(path.to_string()));
+        }
+        let index = self.add(parent_index, Entry::Directory(name.to_string()))?;
+        // A directory we just made is known to be empty
+        self.mark_listed(index);
+        Ok(index)
+    }
+
+    // Creates a file, or sets the size of one that already exists.
+    pub fn touch(&mut self, path: &str, size: usize) -> Result<usize, FileSystemErr> {
+        let (parent, name) = FileSystem::split_parent(path)?;
+        let parent_index = self.resolve(parent)?;
+        let existing_index = self.find_child(parent_index, name).map(|node| node.self_index);
+        match existing_index {
+            Some(index) => match &mut self.nodes[index].entry {
+                Entry::File(_, existing_size) => {
+                    *existing_size = size;
+                    self.sizes.take();
+                    Ok(index)
+                }
+                Entry::Directory(_) => Err(FileSystemErr::IsADirectory(path.to_string()))
+            },
+            None => self.add(parent_index, Entry::File(name.to_string(), size))
+        }
+    }
+
+    // Removes a file, or with `recursive` a directory and everything in it. Removing the current
+    // directory, or one of its ancestors, moves the current directory up to the removed entry's parent.
+    pub fn rm(&mut self, path: &str, recursive: bool) -> Result<(), FileSystemErr> {
+        let index = self.resolve_entry(path)?;
+        let parent_index = match self.nodes[index].parent_index {
+            Some(parent_index) => parent_index,
+            None => return Err(FileSystemErr::InvalidName(path.to_string()))
+        };
+        if !recursive && matches!(self.nodes[index].entry, Entry::Directory(_)) {
+            return Err(FileSystemErr::IsADirectory(path.to_string()));
+        }
+
+        if self.is_within(self.current_index, index) {
+            self.current_index = parent_index;
+        }
+        self.detach(index);
+        Ok(())
+    }
+
+    // Moves an entry into an existing directory, or to a new path and name.
+    pub fn mv(&mut self, source: &str, destination: &str) -> Result<usize, FileSystemErr> {
+        let index = self.resolve_entry(source)?;
+        if self.nodes[index].parent_index.is_none() {
+            return Err(FileSystemErr::InvalidName(source.to_string()));
+        }
+
+        let (parent_index, name) = match self.resolve(destination) {
+            Ok(directory_index) => (directory_index, self.nodes[index].entry.name().to_string()),
+            Err(FileSystemErr::DirectoryNotFound(_)) | Err(FileSystemErr::NotADirectory(_)) => {
+                let (parent, name) = FileSystem::split_parent(destination)?;
+                (self.resolve(parent)?, name.to_string())
+            }
+            Err(err) => return Err(err)
+        };
+
+        if self.is_within(parent_index, index) {
+            return Err(FileSystemErr::InvalidMove { source: source.to_string(), destination: destination.to_string() });
+        }
+        if let Some(existing) = self.find_child(parent_index, &name) {
+            if existing.self_index == index {
+                return Ok(index);
+            }
+            return Err(FileSystemErr::AlreadyExists(self.path_of(existing.self_index)));
+        }
+
+        self.detach(index);
+        match &mut self.nodes[index].entry {
+            Entry::Directory(entry_name) | Entry::File(entry_name, _) => *entry_name = name.clone()
+        }
+        self.nodes[index].parent_index = Some(parent_index);
+        self.nodes[parent_index].child_node_indices.push(index);
+        self.children_by_name.insert((parent_index, name), index);
+        Ok(index)
+    }
+
+    // Whether `index` is `ancestor_index` or somewhere underneath it.
+    fn is_within(&self, index: usize, ancestor_index: usize) -> bool {
+        let mut current = Some(index);
+        while let Some(idx) = current {
+            if idx == ancestor_index {
+                return true;
+            }
+            current = self.nodes[idx].parent_index;
+        }
+        false
+    }
+
+    // Unhooks a node from its parent. Its slot in `nodes` stays put, but nothing can reach it.
+    fn detach(&mut self, index: usize) {
+        if let Some(parent_index) = self.nodes[index].parent_index.take() {
+            self.nodes[parent_index].child_node_indices.retain(|child_index| *child_index != index);
+            self.children_by_name.remove(&(parent_index, self.nodes[index].entry.name().to_string()));
+        }
+        self.sizes.take();
+    }
+
+    // Sizes of every node, worked out in a single bottom-up pass and kept until the tree changes.
+    fn sizes(&self) -> &Vec<usize> {
+        self.sizes.get_or_init(|| {
+            let mut sizes = vec![0; self.nodes.len()];
+            for idx in self.bottom_up_order() {
+                let node = &self.nodes[idx];
+                if let Entry::File(_, size) = node.entry {
+                    sizes[idx] += size;
+                }
+                if let Some(parent_index) = node.parent_index {
+                    sizes[parent_index] += sizes[idx];
+                }
+            }
+            sizes
+        })
+    }
+
+    // Every node reachable from the root, with children always ahead of their parents.
+    pub fn bottom_up_order(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![0];
+        while let Some(idx) = stack.pop() {
+            order.push(idx);
+            stack.extend(&self.nodes[idx].child_node_indices);
+        }
+        order.reverse();
+        order
+    }
+
+    // The full path of every node, built top-down so each one is its parent's path plus a name.
+    // Nodes that have been removed are left with an empty path.
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = vec![String::new(); self.nodes.len()];
+        paths[0] = "/".to_string();
+        for idx in self.bottom_up_order().into_iter().rev() {
+            for child_index in &self.nodes[idx].child_node_indices {
+                let separator = if idx == 0 { "" } else { "/" };
+                paths[*child_index] = format!("{}{}{}", paths[idx], separator, self.nodes[*child_index].entry.name());
+            }
+        }
+        paths
+    }
+
+    // Directories the transcript never ran `ls` in.
+    pub fn unlisted_dirs(&self) -> Vec<String> {
+        let mut paths = self.paths();
+        self.bottom_up_order().into_iter()
+            .filter(|idx| matches!(self.nodes[*idx].entry, Entry::Directory(_)) && !self.nodes[*idx].listed)
+            .map(|idx| std::mem::take(&mut paths[idx]))
+            .collect()
+    }
+
+    // Directories whose size can't be trusted because they, or something below them, were never listed.
+    pub fn unknown_size_dirs(&self) -> Vec<String> {
+        let mut unknown = vec![false; self.nodes.len()];
+        for idx in self.bottom_up_order() {
+            let node = &self.nodes[idx];
+            if matches!(node.entry, Entry::Directory(_)) && !node.listed {
+                unknown[idx] = true;
+            }
+            if let Some(parent_index) = node.parent_index {
+                unknown[parent_index] |= unknown[idx];
+            }
+        }
+        if !unknown.contains(&true) {
+            return vec![];
+        }
+        self.paths().into_iter()
+            .zip(unknown)
+            .filter_map(|(path, is_unknown)| is_unknown.then_some(path))
+            .collect()
+    }
+
+    pub fn get_entry_size(&self, at_index: usize) -> usize {
+        self.sizes()[at_index]
+    }
+
+    // Sizes of every directory, without building their paths.
+    pub fn get_dir_sizes(&self) -> Vec<usize> {
+        self.bottom_up_order().into_iter()
+            .filter(|idx| matches!(self.nodes[*idx].entry, Entry::Directory(_)))
+            .map(|idx| self.get_entry_size(idx))
+            .collect()
+    }
+
+    pub fn get_dirs_with_sizes(&self) -> Vec<(String, usize)> {
+        let mut paths = self.paths();
+        self.bottom_up_order().into_iter()
+            .filter(|idx| matches!(self.nodes[*idx].entry, Entry::Directory(_)))
+            .map(|idx| (std::mem::take(&mut paths[idx]), self.get_entry_size(idx)))
+            .collect()
+    }
+
+    pub fn get_total_consumed(&self) -> usize {
+        self.get_entry_size(0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FileSystemNode {
+    pub self_index: usize,
+    pub entry: Entry,
+    pub child_node_indices: Vec<usize>,
+    pub parent_index: Option<usize>,
+    pub listed: bool
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        FileSystem {
+            nodes: vec![FileSystemNode { self_index: 0, entry: Entry::Directory("/".into()), child_node_indices: vec![], parent_index: None, listed: false }],
+            current_index: 0,
+            children_by_name: HashMap::new(),
+            expected_output: OutputKind::None,
+            sizes: OnceCell::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A root holding `dirs` directories with `files_per_dir` files of size 1..=files_per_dir each.
+    fn flat_transcript(dirs: usize, files_per_dir: usize) -> String {
+        let mut transcript = String::from("$ cd /\n$ ls\n");
+        for dir in 0..dirs {
+            transcript.push_str(&format!("dir d{}\n", dir));
+        }
+        for dir in 0..dirs {
+            transcript.push_str(&format!("$ cd /d{}\n$ ls\n", dir));
+            for file in 0..files_per_dir {
+                transcript.push_str(&format!("{} f{}\n", file + 1, file));
+            }
+        }
+        transcript
+    }
+
+    // A chain of `depth` directories named `a`, each holding one file of size 1.
+    fn deep_transcript(depth: usize) -> String {
+        let mut transcript = String::from("$ cd /\n");
+        for _ in 0..depth {
+            transcript.push_str("$ ls\ndir a\n1 f\n$ cd a\n");
+        }
+        transcript.push_str("$ ls\n1 f\n");
+        transcript
+    }
+
+    #[test]
+    fn sizes_a_million_entry_flat_transcript() {
+        let (dirs, files_per_dir) = (1000, 1000);
+        let file_system = FileSystem::from_transcript(&flat_transcript(dirs, files_per_dir), None).unwrap();
+        let dir_size = files_per_dir * (files_per_dir + 1) / 2;
+        assert_eq!(file_system.get_total_consumed(), dirs * dir_size);
+
+        let dirs_with_sizes = file_system.get_dirs_with_sizes();
+        assert_eq!(dirs_with_sizes.len(), dirs + 1);
+        for (path, size) in dirs_with_sizes {
+            assert_eq!(size, if path == "/" { dirs * dir_size } else { dir_size }, "{}", path);
+        }
+    }
+
+    #[test]
+    fn sizes_a_deep_transcript() {
+        let depth = 200_000;
+        let file_system = FileSystem::from_transcript(&deep_transcript(depth), None).unwrap();
+        assert_eq!(file_system.get_total_consumed(), depth + 1);
+
+        let mut dir_sizes = file_system.get_dir_sizes();
+        dir_sizes.sort_unstable();
+        assert_eq!(dir_sizes, (1..=depth + 1).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn paths_a_deep_transcript() {
+        // Every path is built, so this stays small enough for the paths to fit in memory
+        let depth = 2000;
+        let file_system = FileSystem::from_transcript(&deep_transcript(depth), None).unwrap();
+        let dirs_with_sizes = file_system.get_dirs_with_sizes();
+        assert_eq!(dirs_with_sizes.len(), depth + 1);
+        for (path, size) in dirs_with_sizes {
+            let level = path.matches('a').count();
+            assert_eq!(path, format!("/{}", vec!["a"; level].join("/")));
+            assert_eq!(size, depth + 1 - level);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum Line {
+    Command(Command),
+    Output(String)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GenericParseErr;
+
+impl FromStr for Line {
+    type Err = GenericParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with('$') {
+            Ok(Line::Command(Command::from_str(s)?))
+        }
+        else {
+            // What the output means depends on the command that produced it, so FileSystem::replay parses it
+            Ok(Line::Output(s.to_string()))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Command {
+    List,
+    ChangeDir(String),
+    MakeDir(String),
+    Touch(String, usize),
+    Remove { path: String, recursive: bool },
+    Move(String, String),
+    DiskUsage
+}
+
+impl FromStr for Command {
+    type Err = GenericParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        //We don't care about the $ since we've already checked if it is present in Line::from_str
+        let command_line = s.strip_prefix('$').ok_or(GenericParseErr)?.trim_start();
+        let (command, args) = command_line.split_once(' ').unwrap_or((command_line, ""));
+        let required_arg = || if args.is_empty() { Err(GenericParseErr) } else { Ok(args.to_string()) };
+
+        match command {
+            "ls" => Ok(Command::List),
+            "cd" => Ok(Command::ChangeDir(required_arg()?)),
+            "mkdir" => Ok(Command::MakeDir(required_arg()?)),
+            "touch" => {
+                let (size, path) = args.split_once(' ').ok_or(GenericParseErr)?;
+                Ok(Command::Touch(path.to_string(), usize::from_str(size).map_err(|_| GenericParseErr)?))
+            }
+            "rm" => match args.split_once(' ').unwrap_or((args, "")) {
+                ("-r", "") => Err(GenericParseErr),
+                ("-r", path) => Ok(Command::Remove { path: path.to_string(), recursive: true }),
+                _ => Ok(Command::Remove { path: required_arg()?, recursive: false })
+            },
+            "mv" => {
+                let (source, destination) = args.split_once(' ').ok_or(GenericParseErr)?;
+                Ok(Command::Move(source.to_string(), destination.to_string()))
+            }
+            "du" => Ok(Command::DiskUsage),
+            _ => Err(GenericParseErr)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Entry {
+    Directory(String),
+    File(String, usize)
+}
+
+impl Entry {
+    pub fn name(&self) -> &str {
+        match self {
+            Entry::Directory(name) | Entry::File(name, _) => name
+        }
+    }
+}
+
+impl FromStr for Entry {
+    type Err = GenericParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut string_split_iter = s.splitn(2, ' ');
+        let dir_or_file_sizes_str = string_split_iter.next().ok_or(GenericParseErr)?;
+        let entry_name = string_split_iter.next().ok_or(GenericParseErr)?;
+        match dir_or_file_sizes_str {
+            "dir" => Ok(Entry::Directory(entry_name.to_string())),
+            _ => {
+                let file_size = usize::from_str(dir_or_file_sizes_str).map_err(|_| GenericParseErr)?;
+                Ok(Entry::File(entry_name.to_string(), file_size))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remove(s: &str) -> Option<(String, bool)> {
+        match Command::from_str(s) {
+            Ok(Command::Remove { path, recursive }) => Some((path, recursive)),
+            _ => None
+        }
+    }
+
+    #[test]
+    fn rm_reads_the_recursive_flag_as_its_own_word() {
+        assert_eq!(remove("$ rm -r a/b"), Some(("a/b".to_string(), true)));
+        assert_eq!(remove("$ rm a b"), Some(("a b".to_string(), false)));
+        assert_eq!(remove("$ rm -rf"), Some(("-rf".to_string(), false)));
+        assert_eq!(remove("$ rm -r"), None);
+        assert_eq!(remove("$ rm -r "), None);
+        assert_eq!(remove("$ rm"), None);
+    }
+}
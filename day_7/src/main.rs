@@ -1,203 +1,82 @@
-use std::str::FromStr;
+mod disk;
+mod file_system;
+mod line;
+mod planner;
+mod report;
 
-#[derive(Debug)]
-enum Line {
-    Command(Command),
-    Entry(Entry)
-}
-
-#[derive(Debug, Clone, Copy)]
-struct GenericParseErr;
+use std::{env, fs, path::Path};
 
-impl FromStr for Line {
-    type Err = GenericParseErr;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.starts_with("$") {
-            Ok(Line::Command(Command::from_str(s).unwrap()))
-        }
-        else {
-            Ok(Line::Entry(Entry::from_str(s).unwrap()))
-        }
-    }
-}
+use file_system::FileSystem;
+use planner::{DeletionPlanner, PlanErr};
 
-#[derive(Debug)]
-enum Command {
-    List,
-    ChangeDir(String)
-}
-
-impl FromStr for Command {
-    type Err = GenericParseErr;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let string_split = s.split(" ");
-        let mut split_iter = string_split.into_iter();
-        split_iter.next(); //We don't care about this since we've already checked if the $ is present in Line::from_str
-
-        let command = split_iter.next().unwrap();
-        match command {
-            "ls" => Ok(Command::List),
-            "cd" => Ok(Command::ChangeDir(split_iter.next().unwrap().to_string())),
-            _ => Err(GenericParseErr)
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-enum Entry {
-    Directory(String),
-    File(String, usize)
-}
-
-impl FromStr for Entry {
-    type Err = GenericParseErr;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let string_split = s.split(" ");
-        let mut string_split_iter = string_split.into_iter();
-        let dir_or_file_sizes_str = string_split_iter.next().unwrap();
-        let entry_name = string_split_iter.next().unwrap();
-        match dir_or_file_sizes_str {
-            "dir" => Ok(Entry::Directory(entry_name.to_string())),
-            _ => {
-                let file_size = usize::from_str(dir_or_file_sizes_str).unwrap();
-                Ok(Entry::File(entry_name.to_string(), file_size))
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let file_system = match args.iter().find_map(|arg| arg.strip_prefix("--disk=")) {
+        Some(root) => FileSystem::from_disk(Path::new(root)).unwrap(),
+        None => {
+            let until = args.iter()
+                .find_map(|arg| arg.strip_prefix("--at-line="))
+                .map(|line| line.parse::<usize>().expect("--at-line must be a number"));
+            let transcript = match args.iter().find_map(|arg| arg.strip_prefix("--input=")) {
+                Some(path) => fs::read_to_string(path).unwrap(),
+                None => include_str!("input.txt").to_string()
+            };
+            match FileSystem::from_transcript(&transcript, until) {
+                Ok(file_system) => file_system,
+                Err((line_number, err)) => panic!("Line {}: {:?}", line_number, err)
             }
         }
-    }
-}
-
-#[derive(Debug)]
-struct FileSystem {
-    nodes: Vec<FileSystemNode>
-}
-
-impl FileSystem {
-    fn add(&mut self, parent_index: usize, entry: Entry) {
-        let current_node_count = self.nodes.len();
-        let child_node = FileSystemNode {
-            self_index: current_node_count,
-            entry: entry,
-            child_node_indices: vec![],
-            parent_index: Some(parent_index)
-        };
-
-        self.nodes[parent_index].child_node_indices.push(current_node_count);
-        self.nodes.push(child_node);
-    }
-
-    fn get_node(&self, idx: usize) -> &FileSystemNode {
-        return &self.nodes[idx];
-    }
-
-    fn find_dir(&self, parent_index: usize, name: &str) -> Option<FileSystemNode> {
-        let parent = &self.nodes[parent_index];
-        for child_idx in &parent.child_node_indices {
-            let child_node = &self.nodes[*child_idx];
-            if let Entry::Directory(dir_name) = child_node.entry.clone() {
-                if dir_name == name {
-                    return Some(child_node.clone());
-                }
+    };
+
+    match args.first().map(String::as_str) {
+        Some("transcript") => return print!("{}", file_system.to_transcript()),
+        Some("tree") => return print!("{}", file_system.tree()),
+        Some("json") => return println!("{}", file_system.to_json()),
+        Some("sizes") => {
+            let mut dirs_with_sizes = file_system.get_dirs_with_sizes();
+            dirs_with_sizes.sort_by(|(a_path, a_size), (b_path, b_size)| b_size.cmp(a_size).then(a_path.cmp(b_path)));
+            for (path, size) in dirs_with_sizes {
+                println!("{}\t{}", size, path);
             }
+            return;
         }
-        None
-    }
-
-    fn get_entry_size(&self, at_index: usize) -> usize {
-        let node = &self.nodes[at_index];
-        let entry = node.entry.clone();
-        match entry {
-            Entry::File(_, size) => size,
-            Entry::Directory(name) => {
-                let total = node.child_node_indices.iter().map(|idx| self.get_entry_size(*idx)).sum();
-                total
-            }
-        } 
-    }
-
-    fn get_dirs_with_sizes(&self) -> Vec<(String, usize)> {
-        self.nodes.iter()
-            .filter(|n| {
-                match n.entry.clone() {
-                    Entry::Directory(_) => true,
-                    _ => false
-                }
-            })
-            .map(|n| { 
-                let entry = n.entry.clone();
-                if let Entry::Directory(dir_name) = entry {
-                    return (dir_name, self.get_entry_size(n.self_index))
-                }
-                ("".into(), 0)
-            }).collect()
-    } 
-
-    fn get_total_consumed(&self) -> usize {
-        self.nodes.iter().filter_map(|n| {
-            match n.entry.clone() {
-                Entry::File(name, size) => Some(size),
-                Entry::Directory(_) => None
-            }
-        }).sum()
-    }
-}
-
-#[derive(Debug, Clone)]
-struct FileSystemNode {
-    self_index: usize,
-    entry: Entry,
-    child_node_indices: Vec<usize>,
-    parent_index: Option<usize>
-}
-
-impl Default for FileSystem {
-    fn default() -> Self {
-        FileSystem {
-            nodes: vec![FileSystemNode { self_index: 0, entry: Entry::Directory("/".into()), child_node_indices: vec![], parent_index: None }]
+        Some("du") => {
+            let max_depth = args.iter()
+                .find_map(|arg| arg.strip_prefix("--max-depth="))
+                .map(|depth| depth.parse::<usize>().expect("--max-depth must be a number"));
+            return print!("{}", file_system.du(max_depth));
         }
+        _ => {}
     }
-}
-
-
-fn main() {
-    let directories_text = include_str!("input.txt");
-    let lines = directories_text.lines().map(|line| Line::from_str(line).unwrap()).collect::<Vec<_>>();
 
-    let mut file_system = FileSystem::default();
-    let mut parent_index = 0 as usize; 
-    for line in lines {
-        match line {
-            Line::Command(command) => {
-                match command {
-                    Command::ChangeDir(dir_name) => {
-                        parent_index = match dir_name.as_str() {
-                            ".." => file_system.get_node(parent_index).parent_index.unwrap(),
-                            otherwise => file_system.find_dir(parent_index, otherwise).unwrap().self_index 
-                        }
-                    },
-                    _ => {} //LS Case is implicitly handled by the case below for entries
-                }
-            },
-            Line::Entry(entry) => {
-                file_system.add(parent_index, entry)
-            } 
-        }
+    let unknown_size_dirs = file_system.unknown_size_dirs();
+    if !unknown_size_dirs.is_empty() {
+        println!("Never listed: {:?}", file_system.unlisted_dirs());
+        println!("Sizes are unknown for: {:?}", unknown_size_dirs);
     }
 
     //Part 1
-    let dirs_with_sizes = file_system.get_dirs_with_sizes();
-    let small_dirs_sum: usize = dirs_with_sizes.iter().filter(|(_, b)| *b <= 100000).map(|(_, b)| *b).sum();
+    let small_dirs_sum: usize = file_system.get_dir_sizes().into_iter().filter(|size| *size <= 100000).sum();
     println!("Sum of smaller directories is: {}", small_dirs_sum);
 
-    let disk_size: usize = 70000000;
-    let space_needed: usize = 30000000;
-    let space_consumed: usize = file_system.get_total_consumed();
-    let space_remaining = disk_size - space_consumed;
+    let planner = args.iter()
+        .filter_map(|arg| arg.strip_prefix("--protect="))
+        .fold(DeletionPlanner::new(70000000, 30000000), |planner, path| planner.protect(path));
 
-    let file_size_to_delete = space_needed - space_remaining;
-    let smallest_delete_size = dirs_with_sizes.iter().filter(|(_, size)| *size >= file_size_to_delete).map(|(_, b)| b).min();
+    match planner.smallest_single(&file_system) {
+        Ok(smallest_delete) => println!("The smallest file to delete is {}", smallest_delete.total),
+        Err(PlanErr::NotEnoughDeletable { required }) => println!("No single deletable directory frees the {} needed", required),
+        Err(err) => println!("Can't pick a directory to delete: {:?}", err)
+    }
 
-    println!("The smallest file to delete is {}", smallest_delete_size.unwrap());
+    match planner.plan(&file_system) {
+        Ok(plan) => {
+            println!("Deleting {:?} frees {} of the {} needed", plan.paths, plan.total, plan.required);
+            if !plan.exact {
+                println!("Sizes were too large to plan byte for byte, so this plan may not be the smallest");
+            }
+        }
+        Err(PlanErr::NotEnoughDeletable { required }) => println!("No set of deletable directories frees the {} needed", required),
+        Err(err) => println!("Can't plan deletions: {:?}", err)
+    }
 }
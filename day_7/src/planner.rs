@@ -0,0 +1,410 @@
+use crate::{file_system::{FileSystem, FileSystemErr}, line::Entry};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanErr {
+    FileSystem(FileSystemErr),
+    DiskTooSmall { disk_size: usize, consumed: usize },
+    NotEnoughDeletable { required: usize }
+}
+
+impl From<FileSystemErr> for PlanErr {
+    fn from(err: FileSystemErr) -> Self {
+        PlanErr::FileSystem(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletionPlan {
+    pub paths: Vec<String>,
+    pub total: usize,
+    pub required: usize,
+    pub exact: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct DeletionPlanner {
+    disk_size: usize,
+    space_needed: usize,
+    protected: Vec<String>,
+    bit_budget: usize
+}
+
+// Default upper limit on the bits held across all of the planner's bitsets, about 128MB.
+const BIT_BUDGET: usize = 1 << 30;
+
+impl DeletionPlanner {
+    pub fn new(disk_size: usize, space_needed: usize) -> Self {
+        DeletionPlanner { disk_size, space_needed, protected: vec![], bit_budget: BIT_BUDGET }
+    }
+
+    // A protected directory can't be deleted, and neither can anything inside it or above it.
+    pub fn protect(mut self, path: &str) -> Self {
+        self.protected.push(path.to_string());
+        self
+    }
+
+    // Lets tests reach unit scaling without allocating the full budget.
+    #[cfg(test)]
+    fn with_bit_budget(mut self, bit_budget: usize) -> Self {
+        self.bit_budget = bit_budget;
+        self
+    }
+
+    pub fn required(&self, file_system: &FileSystem) -> Result<usize, PlanErr> {
+        let consumed = file_system.get_total_consumed();
+        let free = self.disk_size.checked_sub(consumed)
+            .ok_or(PlanErr::DiskTooSmall { disk_size: self.disk_size, consumed })?;
+        Ok(self.space_needed.saturating_sub(free))
+    }
+
+    // The single smallest directory that frees enough space on its own.
+    pub fn smallest_single(&self, file_system: &FileSystem) -> Result<DeletionPlan, PlanErr> {
+        let required = self.required(file_system)?;
+        let deletable = self.deletable(file_system)?;
+        directory_preorder(file_system).into_iter()
+            .filter(|idx| deletable[*idx] && file_system.get_entry_size(*idx) >= required)
+            .min_by_key(|idx| file_system.get_entry_size(*idx))
+            .map(|idx| DeletionPlan {
+                paths: vec![file_system.path_of(idx)],
+                total: file_system.get_entry_size(idx),
+                required,
+                exact: true
+            })
+            .ok_or(PlanErr::NotEnoughDeletable { required })
+    }
+
+    // The set of non-nested directories with the smallest combined size that frees enough space.
+    //
+    // Directories are laid out in preorder so that every subtree is a contiguous run, which turns
+    // the nesting rule into "taking a directory jumps past its subtree". Working backwards, the
+    // totals reachable from each position are a bitset over byte counts, capped at the best single
+    // directory since nothing larger can win. When those bitsets would outgrow `BIT_BUDGET`, sizes
+    // are counted in coarser units (rounded down, so the plan still frees enough) and the result
+    // may be slightly above the true optimum; `exact` reports which case applied.
+    pub fn plan(&self, file_system: &FileSystem) -> Result<DeletionPlan, PlanErr> {
+        let required = self.required(file_system)?;
+        if required == 0 {
+            return Ok(DeletionPlan { paths: vec![], total: 0, required, exact: true });
+        }
+
+        let deletable = self.deletable(file_system)?;
+        let order = directory_preorder(file_system);
+        let count = order.len();
+        let sizes = order.iter().map(|idx| file_system.get_entry_size(*idx)).collect::<Vec<_>>();
+
+        let mut subtree_ends = (0..count).map(|position| position + 1).collect::<Vec<_>>();
+        let mut positions = vec![usize::MAX; file_system.node_count()];
+        for (position, idx) in order.iter().enumerate() {
+            positions[*idx] = position;
+        }
+        for position in (1..count).rev() {
+            let parent_position = positions[file_system.get_node(order[position]).parent_index.unwrap()];
+            subtree_ends[parent_position] = subtree_ends[parent_position].max(subtree_ends[position]);
+        }
+
+        let single = self.smallest_single(file_system).ok();
+        let mut most_reachable = vec![0; count + 1];
+        for position in (0..count).rev() {
+            let take = if deletable[order[position]] { sizes[position] + most_reachable[subtree_ends[position]] } else { 0 };
+            most_reachable[position] = most_reachable[position + 1].max(take);
+        }
+        if most_reachable[0] < required {
+            return Err(PlanErr::NotEnoughDeletable { required });
+        }
+        let upper_bound = single.as_ref().map_or(most_reachable[0], |plan| plan.total);
+
+        let total_bits = most_reachable.iter().map(|most| most.min(&upper_bound) + 1).sum::<usize>();
+        let unit = total_bits.div_ceil(self.bit_budget).max(1);
+        let caps = most_reachable.iter().map(|most| most.min(&upper_bound) / unit).collect::<Vec<_>>();
+        let weights = sizes.iter().map(|size| size / unit).collect::<Vec<_>>();
+        let takeable = (0..count)
+            .map(|position| deletable[order[position]] && sizes[position] <= upper_bound)
+            .collect::<Vec<_>>();
+
+        let mut reachable = vec![vec![]; count + 1];
+        reachable[count] = new_bitset(caps[count]);
+        set_bit(&mut reachable[count], 0);
+        for position in (0..count).rev() {
+            let mut bits = new_bitset(caps[position]);
+            or_shifted(&mut bits, &reachable[position + 1], 0);
+            if takeable[position] {
+                or_shifted(&mut bits, &reachable[subtree_ends[position]], weights[position]);
+            }
+            reachable[position] = bits;
+        }
+
+        let target = required.div_ceil(unit);
+        let best_units = (target..=caps[0]).find(|units| get_bit(&reachable[0], *units));
+
+        let mut chosen = vec![];
+        if let Some(mut remaining) = best_units {
+            let mut position = 0;
+            while remaining > 0 {
+                if get_bit(&reachable[position + 1], remaining) {
+                    position += 1;
+                }
+                else {
+                    chosen.push(order[position]);
+                    remaining -= weights[position];
+                    position = subtree_ends[position];
+                }
+            }
+        }
+        else if single.is_none() {
+            // Rounding sizes down can lose every set that frees enough, so fall back to the
+            // largest deletable set, dropping its biggest members while it still frees enough
+            let mut position = 0;
+            let mut largest = vec![];
+            while position < count {
+                if deletable[order[position]] && sizes[position] > 0 && sizes[position] + most_reachable[subtree_ends[position]] == most_reachable[position] {
+                    largest.push(position);
+                    position = subtree_ends[position];
+                }
+                else {
+                    position += 1;
+                }
+            }
+            largest.sort_by_key(|position| std::cmp::Reverse(sizes[*position]));
+            let mut total = most_reachable[0];
+            largest.retain(|position| {
+                let keep = total - sizes[*position] < required;
+                if !keep {
+                    total -= sizes[*position];
+                }
+                keep
+            });
+            chosen = largest.into_iter().map(|position| order[position]).collect();
+        }
+        let total = chosen.iter().map(|idx| file_system.get_entry_size(*idx)).sum::<usize>();
+
+        match single {
+            Some(single) if chosen.is_empty() || single.total <= total => Ok(DeletionPlan { exact: unit == 1, ..single }),
+            _ => Ok(DeletionPlan {
+                paths: chosen.iter().map(|idx| file_system.path_of(*idx)).collect(),
+                total,
+                required,
+                exact: unit == 1
+            })
+        }
+    }
+
+    fn deletable(&self, file_system: &FileSystem) -> Result<Vec<bool>, PlanErr> {
+        let mut blocked = vec![false; file_system.node_count()];
+        for path in &self.protected {
+            let protected_index = file_system.resolve(path)?;
+
+            let mut stack = vec![protected_index];
+            while let Some(idx) = stack.pop() {
+                blocked[idx] = true;
+                stack.extend(&file_system.get_node(idx).child_node_indices);
+            }
+
+            let mut ancestor = file_system.get_node(protected_index).parent_index;
+            while let Some(idx) = ancestor {
+                blocked[idx] = true;
+                ancestor = file_system.get_node(idx).parent_index;
+            }
+        }
+
+        // Only directories still reachable from the root are candidates, not ones removed by `rm`
+        let mut deletable = vec![false; file_system.node_count()];
+        for idx in directory_preorder(file_system) {
+            deletable[idx] = !blocked[idx];
+        }
+        Ok(deletable)
+    }
+}
+
+// Directory indices in preorder, so every subtree occupies the positions right after its root.
+fn directory_preorder(file_system: &FileSystem) -> Vec<usize> {
+    let mut order = vec![];
+    let mut stack = vec![0];
+    while let Some(idx) = stack.pop() {
+        order.push(idx);
+        stack.extend(file_system.get_node(idx).child_node_indices.iter()
+            .rev()
+            .filter(|child_index| matches!(file_system.get_node(**child_index).entry, Entry::Directory(_))));
+    }
+    order
+}
+
+fn new_bitset(max_value: usize) -> Vec<u64> {
+    vec![0; max_value / 64 + 1]
+}
+
+fn get_bit(bits: &[u64], value: usize) -> bool {
+    bits.get(value / 64).is_some_and(|word| word & (1 << (value % 64)) != 0)
+}
+
+fn set_bit(bits: &mut [u64], value: usize) {
+    bits[value / 64] |= 1 << (value % 64);
+}
+
+// ORs `source << shift` into `target`, dropping anything past the end of `target`.
+fn or_shifted(target: &mut [u64], source: &[u64], shift: usize) {
+    let (word_shift, bit_shift) = (shift / 64, shift % 64);
+    for (source_idx, word) in source.iter().enumerate() {
+        let target_idx = source_idx + word_shift;
+        if target_idx >= target.len() {
+            break;
+        }
+        target[target_idx] |= word << bit_shift;
+        if bit_shift > 0 && target_idx + 1 < target.len() {
+            target[target_idx + 1] |= word >> (64 - bit_shift);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift, so the trees are the same on every run
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+    }
+
+    fn random_tree(rng: &mut Rng, max_size: usize) -> FileSystem {
+        let mut file_system = FileSystem::default();
+        let mut dirs = vec!["/".to_string()];
+        for dir in 0..rng.below(10) + 1 {
+            let path = format!("{}/d{}", dirs[rng.below(dirs.len())].trim_end_matches('/'), dir);
+            file_system.mkdir(&path).unwrap();
+            dirs.push(path);
+        }
+        for file in 0..rng.below(20) {
+            let path = format!("{}/f{}", dirs[rng.below(dirs.len())].trim_end_matches('/'), file);
+            file_system.touch(&path, rng.below(max_size) + 1).unwrap();
+        }
+        file_system
+    }
+
+    fn is_within(file_system: &FileSystem, index: usize, ancestor_index: usize) -> bool {
+        let mut current = Some(index);
+        while let Some(idx) = current {
+            if idx == ancestor_index {
+                return true;
+            }
+            current = file_system.get_node(idx).parent_index;
+        }
+        false
+    }
+
+    // The smallest total of any set of non-nested directories freeing at least `required`.
+    fn brute_force(file_system: &FileSystem, required: usize) -> Option<usize> {
+        let dirs = directory_preorder(file_system);
+        (0..1usize << dirs.len())
+            .map(|mask| (0..dirs.len()).filter(|bit| mask & (1 << bit) != 0).map(|bit| dirs[bit]).collect::<Vec<_>>())
+            .filter(|chosen| chosen.iter().all(|a| chosen.iter().all(|b| a == b || !is_within(file_system, *a, *b))))
+            .map(|chosen| chosen.iter().map(|idx| file_system.get_entry_size(*idx)).sum::<usize>())
+            .filter(|total| *total >= required)
+            .min()
+    }
+
+    fn check_plan(file_system: &FileSystem, plan: &DeletionPlan) {
+        let indices = plan.paths.iter().map(|path| file_system.resolve(path).unwrap()).collect::<Vec<_>>();
+        for a in &indices {
+            for b in &indices {
+                assert!(a == b || !is_within(file_system, *a, *b), "{:?} are nested", plan.paths);
+            }
+        }
+        assert_eq!(indices.iter().map(|idx| file_system.get_entry_size(*idx)).sum::<usize>(), plan.total);
+        assert!(plan.total >= plan.required);
+    }
+
+    #[test]
+    fn plan_matches_brute_force_on_small_trees() {
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        for _ in 0..3000 {
+            let file_system = random_tree(&mut rng, 100);
+            let consumed = file_system.get_total_consumed();
+            let required = rng.below(consumed + 2);
+            // Nothing is free, so everything needed has to be deleted
+            let planner = DeletionPlanner::new(consumed, required);
+
+            match (planner.plan(&file_system), brute_force(&file_system, required)) {
+                (Ok(plan), Some(best)) => {
+                    check_plan(&file_system, &plan);
+                    assert!(plan.exact);
+                    assert_eq!(plan.total, best, "{}", file_system.tree());
+                }
+                (Err(PlanErr::NotEnoughDeletable { .. }), None) => {}
+                (plan, best) => panic!("plan {:?} but brute force found {:?}", plan, best)
+            }
+        }
+    }
+
+    #[test]
+    fn plan_scales_units_when_over_the_bit_budget() {
+        let mut rng = Rng(0x9e3779b97f4a7c15);
+        let mut inexact = 0;
+        for _ in 0..1000 {
+            let file_system = random_tree(&mut rng, 1 << 20);
+            let consumed = file_system.get_total_consumed();
+            if consumed == 0 {
+                continue;
+            }
+            let required = rng.below(consumed) + 1;
+            let plan = DeletionPlanner::new(consumed, required).with_bit_budget(1 << 12).plan(&file_system).unwrap();
+            check_plan(&file_system, &plan);
+
+            let best = brute_force(&file_system, required).unwrap();
+            if plan.exact {
+                assert_eq!(plan.total, best);
+            }
+            else {
+                assert!(plan.total >= best);
+                inexact += 1;
+            }
+        }
+        assert!(inexact > 0, "sizes never got big enough to need scaling");
+
+        // Rounded down, neither /b nor /c reaches the target on its own or together
+        let mut file_system = FileSystem::default();
+        for path in ["/a", "/b", "/c"] {
+            file_system.mkdir(path).unwrap();
+        }
+        for (path, size) in [("/a/x", 1), ("/b/x", 1000), ("/c/x", 1000)] {
+            file_system.touch(path, size).unwrap();
+        }
+        let consumed = file_system.get_total_consumed();
+        let plan = DeletionPlanner::new(consumed, 2000).protect("/a").with_bit_budget(64).plan(&file_system).unwrap();
+        check_plan(&file_system, &plan);
+        assert_eq!((plan.paths, plan.total, plan.exact), (vec!["/b".to_string(), "/c".to_string()], 2000, false));
+    }
+
+    #[test]
+    fn protect_blocks_ancestors_and_descendants() {
+        let mut file_system = FileSystem::default();
+        for path in ["/a", "/a/b", "/a/b/c", "/a/e", "/d"] {
+            file_system.mkdir(path).unwrap();
+        }
+        for (path, size) in [("/a/1", 1), ("/a/b/2", 2), ("/a/b/c/3", 3), ("/a/e/4", 4), ("/d/5", 5)] {
+            file_system.touch(path, size).unwrap();
+        }
+
+        let planner = DeletionPlanner::new(15, 4).protect("/a/b");
+        let deletable = planner.deletable(&file_system).unwrap();
+        let deletable_paths = directory_preorder(&file_system).into_iter()
+            .filter(|idx| deletable[*idx])
+            .map(|idx| file_system.path_of(idx))
+            .collect::<Vec<_>>();
+        assert_eq!(deletable_paths, vec!["/a/e", "/d"]);
+
+        assert_eq!(planner.smallest_single(&file_system).unwrap().paths, vec!["/a/e"]);
+        assert_eq!(planner.plan(&file_system).unwrap().paths, vec!["/a/e"]);
+        let plan = DeletionPlanner::new(15, 8).protect("/a/b").plan(&file_system).unwrap();
+        assert_eq!((plan.paths, plan.total), (vec!["/a/e".to_string(), "/d".to_string()], 9));
+        assert!(matches!(
+            DeletionPlanner::new(15, 10).protect("/a/b").plan(&file_system),
+            Err(PlanErr::NotEnoughDeletable { required: 10 })
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt::Write;
+
+use crate::{file_system::FileSystem, line::Entry};
+
+// One step of the JSON walk, so deep trees don't need a stack frame per level.
+enum JsonStep {
+    Node(usize),
+    Separator,
+    Close
+}
+
+impl FileSystem {
+    // An indented listing in the style of `tree`, with cumulative sizes next to every entry.
+    pub fn tree(&self) -> String {
+        let mut output = format!("/ ({})\n", self.get_entry_size(0));
+        let mut stack = self.tree_children(0, String::new());
+        while let Some((index, prefix, is_last)) = stack.pop() {
+            let node = self.get_node(index);
+            let branch = if is_last { "└── " } else { "├── " };
+            writeln!(output, "{}{}{} ({})", prefix, branch, node.entry.name(), self.get_entry_size(index)).unwrap();
+
+            if let Entry::Directory(_) = node.entry {
+                let child_prefix = format!("{}{}", prefix, if is_last { "    " } else { "│   " });
+                stack.extend(self.tree_children(index, child_prefix));
+            }
+        }
+        output
+    }
+
+    // The children of `index` as tree stack entries, last child first so the first is popped first.
+    fn tree_children(&self, index: usize, prefix: String) -> Vec<(usize, String, bool)> {
+        let children = &self.get_node(index).child_node_indices;
+        children.iter()
+            .enumerate()
+            .rev()
+            .map(|(position, child_index)| (*child_index, prefix.clone(), position + 1 == children.len()))
+            .collect()
+    }
+
+    // Directory totals in the style of `du`, deepest first. A `max_depth` of 0 prints only the root.
+    pub fn du(&self, max_depth: Option<usize>) -> String {
+        let mut output = String::new();
+        let paths = self.paths();
+        // Each directory is pushed once to expand it and again, underneath its children, to print it
+        let mut stack = vec![(0, 0, false)];
+        while let Some((index, depth, expanded)) = stack.pop() {
+            if expanded {
+                if max_depth.is_none_or(|max| depth <= max) {
+                    writeln!(output, "{}\t{}", self.get_entry_size(index), paths[index]).unwrap();
+                }
+                continue;
+            }
+
+            stack.push((index, depth, true));
+            stack.extend(self.get_node(index).child_node_indices.iter()
+                .rev()
+                .filter(|child_index| matches!(self.get_node(**child_index).entry, Entry::Directory(_)))
+                .map(|child_index| (*child_index, depth + 1, false)));
+        }
+        output
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut output = String::new();
+        let paths = self.paths();
+        let mut stack = vec![JsonStep::Node(0)];
+        while let Some(step) = stack.pop() {
+            let index = match step {
+                JsonStep::Node(index) => index,
+                JsonStep::Separator => {
+                    output.push(',');
+                    continue;
+                }
+                JsonStep::Close => {
+                    output.push_str("]}");
+                    continue;
+                }
+            };
+
+            let node = self.get_node(index);
+            let kind = match node.entry {
+                Entry::Directory(_) => "dir",
+                Entry::File(_, _) => "file"
+            };
+            write!(
+                output,
+                "{{\"name\":{},\"path\":{},\"type\":\"{}\",\"size\":{}",
+                json_string(node.entry.name()),
+                json_string(&paths[index]),
+                kind,
+                self.get_entry_size(index)
+            ).unwrap();
+
+            match node.entry {
+                Entry::Directory(_) => {
+                    output.push_str(",\"children\":[");
+                    stack.push(JsonStep::Close);
+                    for (position, child_index) in node.child_node_indices.iter().enumerate().rev() {
+                        stack.push(JsonStep::Node(*child_index));
+                        if position > 0 {
+                            stack.push(JsonStep::Separator);
+                        }
+                    }
+                }
+                Entry::File(_, _) => output.push('}')
+            }
+        }
+        output
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c)
+        }
+    }
+    escaped.push('"');
+    escaped
+}
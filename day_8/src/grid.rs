@@ -0,0 +1,34 @@
+// A row-major map with one value per tree, laid out like the Orchard it was computed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    pub rows: usize,
+    pub columns: usize,
+    pub cells: Vec<T>
+}
+
+impl<T> Grid<T> {
+    pub fn new(rows: usize, columns: usize, value: T) -> Self where T: Clone {
+        Grid { rows, columns, cells: vec![value; rows * columns] }
+    }
+
+    pub fn get(&self, row_idx: usize, col_idx: usize) -> &T {
+        &self.cells[row_idx * self.columns + col_idx]
+    }
+
+    pub fn get_mut(&mut self, row_idx: usize, col_idx: usize) -> &mut T {
+        &mut self.cells[row_idx * self.columns + col_idx]
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid { rows: self.rows, columns: self.columns, cells: self.cells.iter().map(f).collect() }
+    }
+
+    // The (row, column) of the largest value, preferring the first one in reading order on ties.
+    pub fn position_of_max(&self) -> Option<(usize, usize)> where T: Ord {
+        self.cells.iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, value)| *value)
+            .map(|(idx, _)| (idx / self.columns, idx % self.columns))
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::{BTreeSet, BinaryHeap};
+
+use crate::orchard::{Height, Orchard, SightLine};
+
+// The trees someone standing at (row_idx, col_idx) can see with their eyes at `eye_height`. Like
+// the scenic score, each line of sight carries on until it reaches a tree at least as tall as the
+// observer's eyes, which is seen but hides everything behind it. The tree underfoot counts as seen.
+// Returns `None` for positions outside the orchard.
+pub fn visible_from<H: Height>(orchard: &Orchard<H>, row_idx: usize, col_idx: usize, eye_height: H, sight_lines: &[SightLine]) -> Option<BTreeSet<(usize, usize)>> {
+    if row_idx >= orchard.rows || col_idx >= orchard.columns {
+        return None;
+    }
+    let mut visible = BTreeSet::from([(row_idx, col_idx)]);
+    for (orientation, direction) in sight_lines {
+        let mut neighbors = orchard.get_neighbors(row_idx, col_idx, *orientation, *direction);
+        while let Some(height) = neighbors.next() {
+            visible.insert(neighbors.position());
+            if height >= eye_height {
+                break;
+            }
+        }
+    }
+    Some(visible)
+}
+
+// Places observers until every tree has been seen, always picking the spot that sees the most trees
+// nobody has seen yet. This is set cover, so the greedy answer can use a few more observers than the
+// true minimum, but never more than a logarithmic factor more. Views are worked out again whenever
+// they're needed rather than kept, since all of them together can be far bigger than the orchard.
+pub fn observers_to_cover<H: Height>(orchard: &Orchard<H>, eye_height: H, sight_lines: &[SightLine]) -> Vec<(usize, usize)> {
+    let position = |idx: usize| (idx / orchard.columns, idx % orchard.columns);
+    let view = |idx: usize| {
+        let (row_idx, col_idx) = position(idx);
+        visible_from(orchard, row_idx, col_idx, eye_height, sight_lines).unwrap()
+    };
+
+    let mut seen = vec![false; orchard.rows * orchard.columns];
+    let mut unseen_count = seen.len();
+    let mut observers = vec![];
+
+    // Gains only ever shrink, so a stale gain at the top of the heap is refreshed and pushed back
+    // rather than recomputing every candidate each round.
+    let mut candidates = (0..seen.len())
+        .map(|idx| (view(idx).len(), idx))
+        .collect::<BinaryHeap<_>>();
+    while unseen_count > 0 {
+        let (gain, idx) = match candidates.pop() {
+            Some(candidate) => candidate,
+            None => break
+        };
+        let view = view(idx);
+        let current_gain = view.iter().filter(|(r, c)| !seen[r * orchard.columns + c]).count();
+        if current_gain < gain {
+            candidates.push((current_gain, idx));
+            continue;
+        }
+
+        for (r, c) in view {
+            seen[r * orchard.columns + c] = true;
+        }
+        unseen_count -= current_gain;
+        observers.push(position(idx));
+    }
+    observers
+}
@@ -0,0 +1,290 @@
+use std::str::FromStr;
+
+//Mainly used as a throwaway type because we really don't have to worry about invalid format.
+#[derive(Debug)]
+pub struct GenericParseErr;
+
+// Anything a tree's height can be stored in. Single digits from the puzzle format convert from u8.
+pub trait Height: Copy + Ord + FromStr + From<u8> {}
+
+impl<T: Copy + Ord + FromStr + From<u8>> Height for T {}
+
+#[derive(Debug)]
+pub struct Orchard<H: Height = u8> {
+    pub rows: usize,
+    pub columns: usize,
+    data: Vec<H>
+}
+
+impl<H: Height> FromStr for Orchard<H> {
+    type Err = GenericParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s.lines().collect::<Vec<_>>();
+        let number_of_rows = lines.len();
+        let number_of_columns = lines.first().map_or(0, |line| line.len());
+
+        let mut data = Vec::with_capacity(number_of_rows * number_of_columns);
+
+        for line in lines.iter() {
+            if line.len() != number_of_columns {
+                return Err(GenericParseErr);
+            }
+            for char in line.chars() {
+                data.push(H::from(char.to_digit(10).ok_or(GenericParseErr)? as u8))
+            }  
+        }
+
+        Ok(Orchard { rows: number_of_rows, columns: number_of_columns, data })
+    }
+}
+
+impl<H: Height> Orchard<H> {
+    // The alternate input format, one row per line with heights separated by commas and/or
+    // whitespace, for heights that don't fit in a single digit.
+    pub fn parse_delimited(s: &str) -> Result<Self, GenericParseErr> {
+        let rows = s.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|cell| !cell.is_empty())
+                .map(|cell| cell.parse::<H>().map_err(|_| GenericParseErr))
+                .collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let number_of_columns = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != number_of_columns) {
+            return Err(GenericParseErr);
+        }
+
+        Ok(Orchard { rows: rows.len(), columns: number_of_columns, data: rows.into_iter().flatten().collect() })
+    }
+
+    // A pseudo-random forest of heights 0-9, for trying out grids bigger than the puzzle input.
+    pub fn generate(rows: usize, columns: usize, seed: u64) -> Self {
+        let mut state = seed.max(1);
+        let data = (0..rows * columns)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                H::from((state % 10) as u8)
+            })
+            .collect();
+
+        Orchard { rows, columns, data }
+    }
+
+    pub fn get_value(&self, row_idx: usize, col_idx: usize) -> H {
+        let index = row_idx * self.columns + col_idx;
+
+        self.data[index]
+    }
+
+    // Every line of trees running along `orientation`: rows for Row, columns for Column, and the
+    // top-left to bottom-right or top-right to bottom-left diagonals for Diagonal and AntiDiagonal.
+    pub fn lines_along(&self, orientation: OrchardIterDirection) -> OrchardIter<'_, H> {
+        OrchardIter {
+            direction: orientation,
+            orchard: self,
+            index: 0
+        }
+    }
+
+    fn line_count(&self, orientation: OrchardIterDirection) -> usize {
+        match orientation {
+            OrchardIterDirection::Row => self.rows,
+            OrchardIterDirection::Column => self.columns,
+            OrchardIterDirection::Diagonal | OrchardIterDirection::AntiDiagonal => (self.rows + self.columns).saturating_sub(1)
+        }
+    }
+
+    // Where a vector varying along `direction` starts, and how many trees it covers. Diagonals are
+    // numbered from the top-right corner for Diagonal and from the top-left corner for AntiDiagonal.
+    fn line_start_and_length(&self, direction: OrchardIterDirection, fixed_index: usize) -> ((usize, usize), usize) {
+        match direction {
+            OrchardIterDirection::Row => ((0, fixed_index), self.rows),
+            OrchardIterDirection::Column => ((fixed_index, 0), self.columns),
+            OrchardIterDirection::Diagonal => {
+                let start = if fixed_index < self.columns { (0, self.columns - 1 - fixed_index) } else { (fixed_index + 1 - self.columns, 0) };
+                (start, usize::min(self.rows - start.0, self.columns - start.1))
+            }
+            OrchardIterDirection::AntiDiagonal => {
+                let start = if fixed_index < self.columns { (0, fixed_index) } else { (fixed_index + 1 - self.columns, self.columns - 1) };
+                (start, usize::min(self.rows - start.0, start.1 + 1))
+            }
+        }
+    }
+
+    fn line_cell(&self, direction: OrchardIterDirection, fixed_index: usize, position: usize) -> (usize, usize) {
+        let ((start_row, start_col), _) = self.line_start_and_length(direction, fixed_index);
+        match direction {
+            OrchardIterDirection::Row => (position, start_col),
+            OrchardIterDirection::Column => (start_row, position),
+            OrchardIterDirection::Diagonal => (start_row + position, start_col + position),
+            OrchardIterDirection::AntiDiagonal => (start_row + position, start_col - position)
+        }
+    }
+
+    pub fn get_neighbors(&self, row_idx: usize, col_idx: usize, orientation: OrchardIterDirection, direction: TreeNeighborsIterDirection) -> TreeNeighborsIter<'_, H> {
+        TreeNeighborsIter { orchard: self, orientation, direction, row_idx, col_idx }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrchardIterDirection {
+    Row,
+    Column,
+    Diagonal,
+    AntiDiagonal
+}
+
+pub struct OrchardIter<'a, H: Height> {
+    orchard: &'a Orchard<H>,
+    direction: OrchardIterDirection, 
+    index: usize
+}
+
+impl<'a, H: Height> Iterator for OrchardIter<'a, H> {
+    type Item = OrchardVecIter<'a, H>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.orchard.line_count(self.direction) {
+            return None;
+        }
+
+        let direction = match self.direction {
+            OrchardIterDirection::Row => OrchardIterDirection::Column,
+            OrchardIterDirection::Column => OrchardIterDirection::Row,
+            diagonal => diagonal
+        };
+        let (_, upper_index) = self.orchard.line_start_and_length(direction, self.index);
+        let result = Some(OrchardVecIter {
+            orchard: self.orchard,
+            fixed_index: self.index,
+            lower_index: None,
+            upper_index,
+            direction
+        });
+        self.index += 1;
+        
+        result
+    }
+}
+
+//This class is responsible for iterating along a row, column or diagonal vector
+pub struct OrchardVecIter<'a, H: Height> {
+    orchard: &'a Orchard<H>,
+    fixed_index: usize,
+    lower_index: Option<usize>,
+    upper_index: usize,
+    direction: OrchardIterDirection
+}
+
+impl<'a, H: Height> OrchardVecIter<'a, H> {
+    // The (row, column) of every tree on the full vector, in forward order.
+    pub fn positions(&self) -> Vec<(usize, usize)> {
+        let (_, length) = self.orchard.line_start_and_length(self.direction, self.fixed_index);
+        (0..length).map(|position| self.orchard.line_cell(self.direction, self.fixed_index, position)).collect()
+    }
+}
+
+impl<'a, H: Height> Iterator for OrchardVecIter<'a, H> {
+    type Item = H;
+
+    fn next(&mut self) -> Option<Self::Item> {  
+
+        self.lower_index = match self.lower_index {
+            Some(idx) => Some(idx + 1),
+            None => Some(0)
+        };
+
+        if self.lower_index.unwrap() == self.upper_index {
+            return None
+        }
+
+        let (row_index, column_index) = self.orchard.line_cell(self.direction, self.fixed_index, self.lower_index.unwrap());
+
+        Some(self.orchard.get_value(row_index, column_index))
+    } 
+}
+
+impl<'a, H: Height> DoubleEndedIterator for OrchardVecIter<'a, H> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if (self.lower_index.is_some() && self.lower_index.unwrap() == self.upper_index) || self.upper_index == 0 {
+            return None;
+        }
+
+        self.upper_index -= 1;
+        let (row_index, column_index) = self.orchard.line_cell(self.direction, self.fixed_index, self.upper_index);
+
+        Some(self.orchard.get_value(row_index, column_index))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeNeighborsIterDirection {
+    Forward,
+    Backward
+}
+
+// A line of sight: which way the line runs and which end of it we're looking towards.
+pub type SightLine = (OrchardIterDirection, TreeNeighborsIterDirection);
+
+pub const ORTHOGONAL_SIGHT_LINES: [SightLine; 4] = [
+    (OrchardIterDirection::Row, TreeNeighborsIterDirection::Forward),
+    (OrchardIterDirection::Row, TreeNeighborsIterDirection::Backward),
+    (OrchardIterDirection::Column, TreeNeighborsIterDirection::Forward),
+    (OrchardIterDirection::Column, TreeNeighborsIterDirection::Backward)
+];
+
+pub const ALL_SIGHT_LINES: [SightLine; 8] = [
+    (OrchardIterDirection::Row, TreeNeighborsIterDirection::Forward),
+    (OrchardIterDirection::Row, TreeNeighborsIterDirection::Backward),
+    (OrchardIterDirection::Column, TreeNeighborsIterDirection::Forward),
+    (OrchardIterDirection::Column, TreeNeighborsIterDirection::Backward),
+    (OrchardIterDirection::Diagonal, TreeNeighborsIterDirection::Forward),
+    (OrchardIterDirection::Diagonal, TreeNeighborsIterDirection::Backward),
+    (OrchardIterDirection::AntiDiagonal, TreeNeighborsIterDirection::Forward),
+    (OrchardIterDirection::AntiDiagonal, TreeNeighborsIterDirection::Backward)
+];
+
+pub struct TreeNeighborsIter<'a, H: Height> {
+    orchard: &'a Orchard<H>,
+    orientation: OrchardIterDirection,
+    direction: TreeNeighborsIterDirection,
+    row_idx: usize,
+    col_idx: usize
+}
+
+impl<'a, H: Height> TreeNeighborsIter<'a, H> {
+    // The (row, column) of the tree most recently returned by `next`.
+    pub fn position(&self) -> (usize, usize) {
+        (self.row_idx, self.col_idx)
+    }
+}
+
+impl<'a, H: Height> Iterator for TreeNeighborsIter<'a, H> {
+    type Item = H;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (row_idx, col_idx) = match (self.orientation, self.direction) { 
+            (OrchardIterDirection::Row, TreeNeighborsIterDirection::Forward) => (Some(self.row_idx), self.col_idx.checked_add(1)),
+            (OrchardIterDirection::Row, TreeNeighborsIterDirection::Backward) => (Some(self.row_idx), self.col_idx.checked_sub(1)),
+            (OrchardIterDirection::Column, TreeNeighborsIterDirection::Forward) => (self.row_idx.checked_add(1), Some(self.col_idx)),
+            (OrchardIterDirection::Column, TreeNeighborsIterDirection::Backward) => (self.row_idx.checked_sub(1), Some(self.col_idx)),
+            (OrchardIterDirection::Diagonal, TreeNeighborsIterDirection::Forward) => (self.row_idx.checked_add(1), self.col_idx.checked_add(1)),
+            (OrchardIterDirection::Diagonal, TreeNeighborsIterDirection::Backward) => (self.row_idx.checked_sub(1), self.col_idx.checked_sub(1)),
+            (OrchardIterDirection::AntiDiagonal, TreeNeighborsIterDirection::Forward) => (self.row_idx.checked_add(1), self.col_idx.checked_sub(1)),
+            (OrchardIterDirection::AntiDiagonal, TreeNeighborsIterDirection::Backward) => (self.row_idx.checked_sub(1), self.col_idx.checked_add(1))
+        };
+        
+        match (row_idx, col_idx) {
+            (Some(row_idx), Some(col_idx)) if row_idx < self.orchard.rows && col_idx < self.orchard.columns => {
+                self.row_idx = row_idx;
+                self.col_idx = col_idx;
+                Some(self.orchard.get_value(row_idx, col_idx))
+            }
+            _ => None
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt::Write;
+
+use crate::grid::Grid;
+
+const GRAYSCALE_RAMP_START: u64 = 232;
+const GRAYSCALE_RAMP_LEVELS: u64 = 24;
+const HIGHLIGHT_COLOR: u64 = 196;
+
+// Scales a value into 0..=max_level, relative to the largest value in the grid.
+fn level(value: u64, largest: u64, max_level: u64) -> u64 {
+    (value as u128 * max_level as u128).checked_div(largest as u128).unwrap_or(0) as u64
+}
+
+// A plain (P2) PGM heatmap. Values are scaled to 0-254 so the highlighted cell, if any, is the
+// only pure white pixel in the image.
+pub fn to_pgm(grid: &Grid<u64>, highlight: Option<(usize, usize)>) -> String {
+    let largest = grid.cells.iter().copied().max().unwrap_or(0);
+    let mut output = format!("P2\n{} {}\n255\n", grid.columns, grid.rows);
+    for row_idx in 0..grid.rows {
+        let line = (0..grid.columns)
+            .map(|col_idx| match highlight {
+                Some(position) if position == (row_idx, col_idx) => 255,
+                _ => level(*grid.get(row_idx, col_idx), largest, 254)
+            })
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(output, "{}", line).unwrap();
+    }
+    output
+}
+
+// Two terminal cells per tree on a grayscale background ramp, with the highlighted tree in red.
+pub fn to_ansi(grid: &Grid<u64>, highlight: Option<(usize, usize)>) -> String {
+    let largest = grid.cells.iter().copied().max().unwrap_or(0);
+    let mut output = String::new();
+    for row_idx in 0..grid.rows {
+        for col_idx in 0..grid.columns {
+            let color = match highlight {
+                Some(position) if position == (row_idx, col_idx) => HIGHLIGHT_COLOR,
+                _ => GRAYSCALE_RAMP_START + level(*grid.get(row_idx, col_idx), largest, GRAYSCALE_RAMP_LEVELS - 1)
+            };
+            write!(output, "\x1b[48;5;{}m  ", color).unwrap();
+        }
+        output.push_str("\x1b[0m\n");
+    }
+    output
+}
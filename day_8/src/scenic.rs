@@ -0,0 +1,135 @@
+use crate::{grid::Grid, orchard::{Height, Orchard, SightLine, TreeNeighborsIter, TreeNeighborsIterDirection}};
+
+fn get_visible_tree_count<H: Height>(height: H, neighbors_iter: TreeNeighborsIter<H>) -> usize {
+    let mut total = 0;
+    for neighbor in neighbors_iter {
+        total += 1;
+        if neighbor >= height {
+            break;
+        }
+    }
+    total
+}
+
+// The original approach: walk outward from every tree until something blocks the view.
+pub fn scenic_scores_by_walking<H: Height>(orchard: &Orchard<H>, sight_lines: &[SightLine]) -> Grid<usize> {
+    let mut scenic_scores = Grid::new(orchard.rows, orchard.columns, 0usize);
+    for row_idx in 0..orchard.rows {
+        for col_idx in 0..orchard.columns {
+            *scenic_scores.get_mut(row_idx, col_idx) = walked_score(orchard, row_idx, col_idx, sight_lines);
+        }
+    }
+    scenic_scores
+}
+
+fn walked_score<H: Height>(orchard: &Orchard<H>, row_idx: usize, col_idx: usize, sight_lines: &[SightLine]) -> usize {
+    let height = orchard.get_value(row_idx, col_idx);
+    sight_lines.iter()
+        .map(|(o, d)| get_visible_tree_count(height, orchard.get_neighbors(row_idx, col_idx, *o, *d)))
+        .product()
+}
+
+// How far each tree can see back towards the start of the line. The stack holds the positions of
+// trees that haven't been outgrown yet, so the first one left at least as tall as the current
+// tree is what blocks it. Every position is pushed and popped once, so this is linear.
+fn viewing_distances<H: Height>(line: impl Iterator<Item = H>) -> Vec<usize> {
+    let mut distances = vec![];
+    let mut stack: Vec<(usize, H)> = vec![];
+    for (idx, height) in line.enumerate() {
+        while stack.last().is_some_and(|(_, blocker)| *blocker < height) {
+            stack.pop();
+        }
+        distances.push(match stack.last() {
+            Some((blocker_idx, _)) => idx - blocker_idx,
+            None => idx
+        });
+        stack.push((idx, height));
+    }
+    distances
+}
+
+// Scores every tree with one pass over each line per sight line.
+pub fn scenic_scores<H: Height>(orchard: &Orchard<H>, sight_lines: &[SightLine]) -> Grid<usize> {
+    let mut scenic_scores = Grid::new(orchard.rows, orchard.columns, 1usize);
+    for (orientation, direction) in sight_lines {
+        for line in orchard.lines_along(*orientation) {
+            let positions = line.positions();
+            // Looking forward along a line is looking backward along the reversed line
+            let distances = match direction {
+                TreeNeighborsIterDirection::Backward => viewing_distances(line),
+                TreeNeighborsIterDirection::Forward => viewing_distances(line.rev())
+            };
+
+            for (idx, distance) in distances.into_iter().enumerate() {
+                let (row_idx, col_idx) = match direction {
+                    TreeNeighborsIterDirection::Backward => positions[idx],
+                    TreeNeighborsIterDirection::Forward => positions[positions.len() - idx - 1]
+                };
+                *scenic_scores.get_mut(row_idx, col_idx) *= distance;
+            }
+        }
+    }
+    scenic_scores
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{orchard::{ALL_SIGHT_LINES, ORTHOGONAL_SIGHT_LINES}, visibility::visibility_map};
+
+    fn check_against_walking(orchard: &Orchard) {
+        for sight_lines in [&ORTHOGONAL_SIGHT_LINES[..], &ALL_SIGHT_LINES[..]] {
+            assert_eq!(scenic_scores(orchard, sight_lines), scenic_scores_by_walking(orchard, sight_lines));
+        }
+    }
+
+    #[test]
+    fn matches_walking_on_the_puzzle_input() {
+        check_against_walking(&Orchard::from_str(include_str!("input.txt")).unwrap());
+    }
+
+    #[test]
+    fn matches_walking_on_the_small_input() {
+        let orchard = Orchard::from_str(include_str!("small_input.txt")).unwrap();
+        check_against_walking(&orchard);
+        assert_eq!(scenic_scores(&orchard, &ORTHOGONAL_SIGHT_LINES).cells.iter().max(), Some(&8));
+    }
+
+    #[test]
+    fn matches_walking_on_generated_grids() {
+        for (rows, columns, seed) in [(1, 1, 1), (1, 40, 2), (40, 1, 3), (37, 53, 4), (120, 90, 5)] {
+            check_against_walking(&Orchard::generate(rows, columns, seed));
+        }
+    }
+
+    #[test]
+    fn generated_5000_by_5000_forest() {
+        let size = 5000;
+        let orchard: Orchard = Orchard::generate(size, size, 2022);
+        let visibility = visibility_map(&orchard, &ORTHOGONAL_SIGHT_LINES);
+        let scores = scenic_scores(&orchard, &ORTHOGONAL_SIGHT_LINES);
+
+        for idx in 0..size {
+            for (row_idx, col_idx) in [(0, idx), (size - 1, idx), (idx, 0), (idx, size - 1)] {
+                assert!(visibility.get(row_idx, col_idx), "border tree ({}, {}) isn't visible", row_idx, col_idx);
+                assert_eq!(*scores.get(row_idx, col_idx), 0);
+            }
+        }
+
+        // Every 97th row and column, checked tree by tree against walking outward
+        for sampled in (1..size - 1).step_by(97) {
+            for other in 0..size {
+                for (row_idx, col_idx) in [(sampled, other), (other, sampled)] {
+                    assert_eq!(*scores.get(row_idx, col_idx), walked_score(&orchard, row_idx, col_idx, &ORTHOGONAL_SIGHT_LINES));
+                    let height = orchard.get_value(row_idx, col_idx);
+                    let visible = ORTHOGONAL_SIGHT_LINES.iter()
+                        .any(|(o, d)| orchard.get_neighbors(row_idx, col_idx, *o, *d).all(|neighbor| neighbor < height));
+                    assert_eq!(*visibility.get(row_idx, col_idx), visible, "({}, {})", row_idx, col_idx);
+                }
+            }
+        }
+        assert!(scores.cells.iter().any(|score| *score > 0));
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{grid::Grid, orchard::{Height, Orchard, SightLine, TreeNeighborsIterDirection}};
+
+fn max_topography_indicator<H: Height>(running_max_opt: &mut Option<H>, current_val: H) -> Option<bool> {    
+    match running_max_opt {
+        None => {
+            *running_max_opt = Some(current_val);
+            return Some(true);
+        },
+        Some(running_max) => {
+            if *running_max < current_val {
+                *running_max_opt = Some(current_val);
+                return Some(true);
+            }
+        }
+    }
+    Some(false)
+}
+
+// Whether each tree can be seen from outside the grid along any of the given sight lines.
+pub fn visibility_map<H: Height>(orchard: &Orchard<H>, sight_lines: &[SightLine]) -> Grid<bool> {
+    let mut tree_visibility = Grid::new(orchard.rows, orchard.columns, false);
+    for (orientation, direction) in sight_lines {
+        for line in orchard.lines_along(*orientation) {
+            let positions = line.positions();
+            let visible = match direction {
+                TreeNeighborsIterDirection::Backward => line.scan(None, max_topography_indicator).collect::<Vec<_>>(),
+                TreeNeighborsIterDirection::Forward => line.rev().scan(None, max_topography_indicator).collect::<Vec<_>>()
+            };
+
+            for (idx, cell) in visible.into_iter().enumerate() {
+                let (row_idx, col_idx) = match direction {
+                    TreeNeighborsIterDirection::Backward => positions[idx],
+                    TreeNeighborsIterDirection::Forward => positions[positions.len() - idx - 1]
+                };
+                *tree_visibility.get_mut(row_idx, col_idx) |= cell;
+            }
+        }
+    }
+    tree_visibility
+}
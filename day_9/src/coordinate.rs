@@ -0,0 +1,12 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coordinate {
+    pub x: i32,
+    pub y: i32
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coordinate3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32
+}
@@ -0,0 +1,118 @@
+use std::{fmt::Debug, str::FromStr};
+
+use crate::{coordinate::Coordinate, moves::GenericParseError};
+
+// Decides where a knot goes after the knot in front of it has moved.
+pub trait FollowPolicy: Debug {
+    fn follow(&self, leader: Coordinate, follower: Coordinate) -> Coordinate;
+}
+
+// The puzzle's rule: once the leader is more than `slack` cells away along either axis, step
+// towards it by one cell along each axis, diagonally if need be. A slack of 1 is the original rope.
+#[derive(Debug, Clone, Copy)]
+pub struct Slack(pub i32);
+
+impl Default for Slack {
+    fn default() -> Self {
+        Slack(1)
+    }
+}
+
+impl FollowPolicy for Slack {
+    fn follow(&self, leader: Coordinate, follower: Coordinate) -> Coordinate {
+        let (x_diff, y_diff) = (leader.x - follower.x, leader.y - follower.y);
+        if i32::abs(x_diff) > self.0 || i32::abs(y_diff) > self.0 {
+            Coordinate { x: follower.x + i32::clamp(x_diff, -1, 1), y: follower.y + i32::clamp(y_diff, -1, 1) }
+        }
+        else {
+            follower
+        }
+    }
+}
+
+// Never moves diagonally. Once the leader is anything other than on top of or right next to the
+// follower, it steps along whichever axis has the larger gap, preferring x on ties, until it's
+// next to the leader again. A diagonal gap takes one step along each axis.
+#[derive(Debug, Clone, Copy)]
+pub struct Manhattan;
+
+impl FollowPolicy for Manhattan {
+    fn follow(&self, leader: Coordinate, mut follower: Coordinate) -> Coordinate {
+        loop {
+            let (x_diff, y_diff) = (leader.x - follower.x, leader.y - follower.y);
+            if i32::abs(x_diff) + i32::abs(y_diff) <= 1 {
+                return follower;
+            }
+            else if i32::abs(x_diff) >= i32::abs(y_diff) {
+                follower.x += x_diff.signum();
+            }
+            else {
+                follower.y += y_diff.signum();
+            }
+        }
+    }
+}
+
+// Once stretched past `slack`, closes `stiffness` (0 to 1) of the gap along each axis, but always at
+// least one cell. Long gaps snap back quickly while short ones drag behind.
+#[derive(Debug, Clone, Copy)]
+pub struct Elastic {
+    pub slack: i32,
+    pub stiffness: f32
+}
+
+impl FollowPolicy for Elastic {
+    fn follow(&self, leader: Coordinate, follower: Coordinate) -> Coordinate {
+        let (x_diff, y_diff) = (leader.x - follower.x, leader.y - follower.y);
+        if i32::abs(x_diff) <= self.slack && i32::abs(y_diff) <= self.slack {
+            return follower;
+        }
+
+        let pull = |diff: i32| {
+            let amount = (diff.abs() as f32 * self.stiffness).round() as i32;
+            amount.clamp(diff.abs().min(1), diff.abs()) * diff.signum()
+        };
+        Coordinate { x: follower.x + pull(x_diff), y: follower.y + pull(y_diff) }
+    }
+}
+
+// Parses `slack:N`, `manhattan` or `elastic:SLACK:STIFFNESS`, as used on the command line.
+pub fn parse_policy(s: &str) -> Result<Box<dyn FollowPolicy>, GenericParseError> {
+    let parts = s.split(':').collect::<Vec<_>>();
+    let number = |n: &str| i32::from_str(n).map_err(|_| GenericParseError);
+    match parts[..] {
+        ["slack", slack] => Ok(Box::new(Slack(number(slack)?))),
+        ["manhattan"] => Ok(Box::new(Manhattan)),
+        ["elastic", slack, stiffness] => Ok(Box::new(Elastic {
+            slack: number(slack)?,
+            stiffness: f32::from_str(stiffness).map_err(|_| GenericParseError)?
+        })),
+        _ => Err(GenericParseError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{moves::Move, rope::Rope};
+
+    #[test]
+    fn manhattan_keeps_up_with_diagonal_moves() {
+        let mut rope = Rope::new(10).with_trace();
+        for knot_idx in 1..10 {
+            rope = rope.with_knot_policy(knot_idx, Box::new(Manhattan));
+        }
+        for m in ["UR 10", "DL 4", "UL 3", "V 3,-7", "DR 6"] {
+            rope.make_move(Move::from_str(m).unwrap());
+        }
+
+        for (step, knots) in rope.trace.unwrap().replay().enumerate() {
+            for (leader, follower) in knots.iter().zip(&knots[1..]) {
+                let distance = i32::abs(leader.x - follower.x) + i32::abs(leader.y - follower.y);
+                assert!(distance <= 1, "step {}: {:?} is too far behind {:?}", step, follower, leader);
+            }
+        }
+    }
+}
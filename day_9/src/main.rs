@@ -1,119 +1,92 @@
-use std::{str::FromStr, collections::HashSet, hash::Hash};
+mod coordinate;
+mod follow;
+mod moves;
+mod render;
+mod rope;
+mod rope3d;
+mod stats;
+mod trace;
 
-#[derive(Clone, Copy, Debug)]
-struct GenericParseError; 
+use std::{env, fs, path::Path, str::FromStr};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash,)]
-struct Coordinate {
-    x: i32,
-    y: i32
-}
+use moves::Move;
+use rope::Rope;
+use rope3d::{Move3, Rope3};
 
-impl Default for Coordinate {
-    fn default() -> Self {
-        Coordinate { x: 0, y: 0 }
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let flag_value = |name: &str| args.iter().find_map(|arg| arg.strip_prefix(name));
+    let input_text = match flag_value("--input=") {
+        Some(path) => fs::read_to_string(path).unwrap(),
+        None => include_str!("input.txt").to_string()
+    };
+
+    if args.iter().any(|arg| arg == "--3d") {
+        let knot_idx = flag_value("--knot=").map_or(9, |knot| knot.parse::<usize>().unwrap());
+        let mut rope = Rope3::new(10);
+        for line in input_text.lines() {
+            rope.make_move(Move3::from_str(line).unwrap());
+        }
+        println!("Knot {} of the 3D rope visited {} distinct cells", knot_idx, rope.visited_positions(knot_idx).len());
+        return;
     }
-}
-
-#[derive(Clone, Copy)]
-enum Move {
-    X(i32),
-    Y(i32)
-}
-
-impl FromStr for Move {
-    type Err = GenericParseError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split_iter = s.split(" ");
-        let direction_char = split_iter.next().unwrap();
-        let number =  i32::from_str(split_iter.next().unwrap()).unwrap();
+    let input = input_text.lines().map(|x| Move::from_str(x).unwrap()).collect::<Vec<_>>();
 
-        match direction_char {
-            "L" => Ok(Move::X(-number)),
-            "R" => Ok(Move::X(number)),
-            "U" => Ok(Move::Y(number)),
-            "D" => Ok(Move::Y(-number)),
-            _ => Err(GenericParseError)
-        }
+    let mut short_rope = Rope::default();
+    for rope_move in input.iter() {
+        short_rope.make_move(*rope_move);
     }
-}
+    println!("Short rope visited {} distinct spaces.", short_rope.visited_positions.len());
 
-#[derive(Debug)]
-struct Rope {
-    coordinates: Vec<Coordinate>,
-    visited_positions: HashSet<Coordinate>
-}
+    let show_knot_stats = args.iter().any(|arg| arg == "--knot-stats");
+    let show_ascii = args.iter().any(|arg| arg == "--ascii");
+    let frames_dir = flag_value("--frames-dir=");
 
-impl Default for Rope {
-    fn default() -> Self {
-        Rope {
-            coordinates: vec![Coordinate::default(); 2],
-            visited_positions: HashSet::new()
-        }
+    let mut long_rope = Rope::new(10);
+    if show_ascii || frames_dir.is_some() {
+        long_rope = long_rope.with_trace();
     }
-}
-
-impl Rope {
-    fn new(len: usize) -> Self {
-        Rope {
-            coordinates: vec![Coordinate::default(); len],
-            visited_positions: HashSet::new()
-        }
+    if show_knot_stats {
+        long_rope = long_rope.with_knot_tracking();
     }
-
-    fn step(&mut self, direction: Move) {
-        match direction {
-            Move::X(step) => {
-                self.coordinates[0].x += step;
-            },
-            Move::Y(step) => {
-                self.coordinates[0].y += step;
+    // --policy=... sets every knot, --knot-policy=N:... then overrides knot N
+    for arg in args.iter() {
+        if let Some(policy) = arg.strip_prefix("--policy=") {
+            for knot_idx in 1..10 {
+                long_rope = long_rope.with_knot_policy(knot_idx, follow::parse_policy(policy).unwrap());
             }
         }
-
-        for coord_indx in 1..self.coordinates.len() {
-            let (x_diff, y_diff) = (self.coordinates[coord_indx - 1].x - self.coordinates[coord_indx].x, self.coordinates[coord_indx - 1].y - self.coordinates[coord_indx].y);
-            if i32::abs(x_diff) > 1 || i32::abs(y_diff) > 1 {
-                self.coordinates[coord_indx].x += i32::clamp(x_diff, -1, 1);
-                self.coordinates[coord_indx].y += i32::clamp(y_diff, -1, 1);
-            }
-        }
-
-        self.visited_positions.insert(self.coordinates.last().unwrap().clone());
-    } 
-
-    fn make_move(&mut self, m: Move) {
-        match m {
-            Move::X(total) => {
-                for unused in 0..i32::abs(total) {
-                    self.step(Move::X(1 * i32::signum(total)))
-                }
-            },
-            Move::Y(total) => {
-                for unused in 0..i32::abs(total) {
-                    self.step(Move::Y(1 * i32::signum(total)))
-                }
-            }
+        else if let Some((knot_idx, policy)) = arg.strip_prefix("--knot-policy=").and_then(|p| p.split_once(':')) {
+            long_rope = long_rope.with_knot_policy(knot_idx.parse().unwrap(), follow::parse_policy(policy).unwrap());
         }
     }
-}
-
-fn main() {
-    let input = include_str!("input.txt").lines().map(|x| Move::from_str(x).unwrap()).collect::<Vec<_>>();
-
-    println!("For head and tail");
-    let mut short_rope = Rope::default();
     for rope_move in input.iter() {
-        short_rope.make_move(rope_move.clone());
-
-        println!("{:?} {:?}", short_rope.coordinates[0], short_rope.coordinates[1])
+        long_rope.make_move(*rope_move);
+    }
+    println!("Long rope visiited {} distinct spaces", long_rope.visited_positions.len());
+
+    if show_knot_stats {
+        for (knot_idx, stats) in long_rope.knot_stats.iter().flatten().enumerate() {
+            let (min, max) = stats.bounding_box();
+            let (most_visited, visits) = stats.most_visited();
+            println!(
+                "Knot {}: {} cells within ({}, {})..({}, {}), most visited ({}, {}) {} times, left the origin on step {}",
+                knot_idx, stats.distinct_cells(), min.x, min.y, max.x, max.y, most_visited.x, most_visited.y, visits,
+                stats.first_departure.map_or("never".to_string(), |step| step.to_string())
+            );
+        }
     }
-    println!("Short rope visited {} distinct spaces.", short_rope.visited_positions.len());
 
-    let mut long_rope = Rope::new(10);
-    for rope_move in input.iter() {
-        long_rope.make_move(rope_move.clone());
+    if let Some(trace) = long_rope.trace.as_ref() {
+        if show_ascii {
+            for frame in render::ascii_frames(trace) {
+                println!("{}", frame);
+            }
+        }
+        if let Some(directory) = frames_dir {
+            render::write_pgm_frames(trace, Path::new(directory)).unwrap();
+            println!("Wrote {} frames to {}", trace.len() + 1, directory);
+        }
     }
-    println!("Long rope visiited {} distinct spaces", long_rope.visited_positions.len())
 }
@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GenericParseError; 
+
+#[derive(Clone, Copy, Debug)]
+pub enum Move {
+    X(i32),
+    Y(i32),
+    // Any displacement, from diagonals like `UL 3` or explicit vectors like `V 3,-2`
+    Vector(i32, i32),
+    // Quarter turns clockwise (negative for anticlockwise) applied to every later move
+    Rotate(i32)
+}
+
+impl FromStr for Move {
+    type Err = GenericParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut split_iter = s.split(' ');
+        let direction_char = split_iter.next().ok_or(GenericParseError)?;
+        let argument = split_iter.next().ok_or(GenericParseError)?;
+        if direction_char == "V" {
+            let (x, y) = argument.split_once(',').ok_or(GenericParseError)?;
+            let parse = |n: &str| i32::from_str(n.trim()).map_err(|_| GenericParseError);
+            return Ok(Move::Vector(parse(x)?, parse(y)?));
+        }
+
+        let number = i32::from_str(argument).map_err(|_| GenericParseError)?;
+        match direction_char {
+            "L" => Ok(Move::X(-number)),
+            "R" => Ok(Move::X(number)),
+            "U" => Ok(Move::Y(number)),
+            "D" => Ok(Move::Y(-number)),
+            "UL" => Ok(Move::Vector(-number, number)),
+            "UR" => Ok(Move::Vector(number, number)),
+            "DL" => Ok(Move::Vector(-number, -number)),
+            "DR" => Ok(Move::Vector(number, -number)),
+            "CW" => Ok(Move::Rotate(number)),
+            "CCW" => Ok(Move::Rotate(-number)),
+            _ => Err(GenericParseError)
+        }
+    }
+}
+
+impl Move {
+    // The displacement this move makes once `quarter_turns` clockwise rotations are applied.
+    pub fn displacement(self, quarter_turns: i32) -> (i32, i32) {
+        let (x, y) = match self {
+            Move::X(x) => (x, 0),
+            Move::Y(y) => (0, y),
+            Move::Vector(x, y) => (x, y),
+            Move::Rotate(_) => (0, 0)
+        };
+        match quarter_turns.rem_euclid(4) {
+            0 => (x, y),
+            1 => (y, -x),
+            2 => (-x, -y),
+            _ => (-y, x)
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::{collections::HashSet, fmt::Write, fs, io, path::Path};
+
+use crate::{coordinate::Coordinate, trace::Trace};
+
+// The head is H, the other knots are numbered (letters past 9), the start is s and cells the
+// tail has already been through are #. Knots closer to the head are drawn over later ones.
+fn knot_symbol(knot_idx: usize) -> char {
+    match knot_idx {
+        0 => 'H',
+        idx => char::from_digit(idx as u32 % 36, 36).unwrap()
+    }
+}
+
+fn draw_frame(knots: &[Coordinate], trail: &HashSet<Coordinate>, start: Coordinate, bounds: (Coordinate, Coordinate)) -> Vec<Vec<char>> {
+    let (min, max) = bounds;
+    let mut rows = vec![vec!['.'; (max.x - min.x + 1) as usize]; (max.y - min.y + 1) as usize];
+    // y grows upwards, so the top row of the frame is the largest y
+    let mut plot = |position: &Coordinate, symbol: char| rows[(max.y - position.y) as usize][(position.x - min.x) as usize] = symbol;
+
+    for position in trail {
+        plot(position, '#');
+    }
+    plot(&start, 's');
+    for (knot_idx, knot) in knots.iter().enumerate().rev() {
+        plot(knot, knot_symbol(knot_idx));
+    }
+    rows
+}
+
+// Every frame of the trace as text, drawn one at a time as the iterator is advanced.
+pub fn ascii_frames(trace: &Trace) -> impl Iterator<Item = String> + '_ {
+    let bounds = trace.bounds();
+    let mut trail = HashSet::new();
+    let mut start = None;
+    trace.replay()
+        .map(move |knots| {
+            let tail = *knots.last().unwrap();
+            trail.insert(tail);
+            let start = *start.get_or_insert(knots[0]);
+            let mut frame = String::new();
+            for row in draw_frame(&knots, &trail, start, bounds) {
+                writeln!(frame, "{}", row.into_iter().collect::<String>()).unwrap();
+            }
+            frame
+        })
+}
+
+// Writes one plain PGM image per frame into `directory`, named frame_00000.pgm onwards. Knots are
+// white, fading towards the tail, and the trail is dark gray.
+pub fn write_pgm_frames(trace: &Trace, directory: &Path) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+    let (min, max) = trace.bounds();
+    let (width, height) = ((max.x - min.x + 1) as usize, (max.y - min.y + 1) as usize);
+    let knot_count = trace.knot_count().max(1);
+
+    let mut trail = HashSet::new();
+    for (frame_idx, knots) in trace.replay().enumerate() {
+        trail.insert(*knots.last().unwrap());
+
+        let mut pixels = vec![0u8; width * height];
+        for position in &trail {
+            pixels[(max.y - position.y) as usize * width + (position.x - min.x) as usize] = 64;
+        }
+        for (knot_idx, knot) in knots.iter().enumerate().rev() {
+            let shade = 255 - (knot_idx * 127 / knot_count) as u8;
+            pixels[(max.y - knot.y) as usize * width + (knot.x - min.x) as usize] = shade;
+        }
+
+        let mut image = format!("P2\n{} {}\n255\n", width, height);
+        for row in pixels.chunks(width) {
+            writeln!(image, "{}", row.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(" ")).unwrap();
+        }
+        fs::write(directory.join(format!("frame_{:05}.pgm", frame_idx)), image)?;
+    }
+    Ok(())
+}
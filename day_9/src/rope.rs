@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+
+use crate::{coordinate::Coordinate, follow::{FollowPolicy, Slack}, moves::Move, stats::KnotStats, trace::Trace};
+
+#[derive(Debug)]
+pub struct Rope {
+    pub coordinates: Vec<Coordinate>,
+    pub visited_positions: HashSet<Coordinate>,
+    pub trace: Option<Trace>,
+    pub quarter_turns: i32,
+    pub step_count: usize,
+    pub knot_stats: Option<Vec<KnotStats>>,
+    // How each knot follows the one in front of it. The head has an entry too, but it's never used.
+    policies: Vec<Box<dyn FollowPolicy>>
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Rope::new(2)
+    }
+}
+
+impl Rope {
+    pub fn new(len: usize) -> Self {
+        Rope {
+            coordinates: vec![Coordinate::default(); len],
+            visited_positions: HashSet::new(),
+            trace: None,
+            quarter_turns: 0,
+            step_count: 0,
+            knot_stats: None,
+            policies: (0..len).map(|_| Box::new(Slack::default()) as Box<dyn FollowPolicy>).collect()
+        }
+    }
+
+    // Overrides the follow policy for a single knot.
+    pub fn with_knot_policy(mut self, knot_idx: usize, policy: Box<dyn FollowPolicy>) -> Self {
+        self.policies[knot_idx] = policy;
+        self
+    }
+
+    // Starts recording every knot's position after each step.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Trace::new(&self.coordinates));
+        self
+    }
+
+    // Starts keeping visit statistics for every knot, not just the tail's visited positions.
+    pub fn with_knot_tracking(mut self) -> Self {
+        self.knot_stats = Some(self.coordinates.iter().map(|knot| KnotStats::new(*knot)).collect());
+        self
+    }
+
+    // Moves the head by at most one cell along each axis and lets the rest of the rope follow.
+    fn step(&mut self, x_step: i32, y_step: i32) {
+        self.coordinates[0].x += x_step;
+        self.coordinates[0].y += y_step;
+
+        for coord_indx in 1..self.coordinates.len() {
+            self.coordinates[coord_indx] = self.policies[coord_indx].follow(self.coordinates[coord_indx - 1], self.coordinates[coord_indx]);
+        }
+
+        self.step_count += 1;
+        self.visited_positions.insert(*self.coordinates.last().unwrap());
+        if let Some(knot_stats) = self.knot_stats.as_mut() {
+            for (stats, knot) in knot_stats.iter_mut().zip(&self.coordinates) {
+                stats.record(self.step_count, *knot);
+            }
+        }
+        if let Some(trace) = self.trace.as_mut() {
+            trace.record(&self.coordinates);
+        }
+    } 
+
+    // Walks the head along the move one cell at a time. Moves that aren't straight or exactly
+    // diagonal follow the closest grid line to the true path, so the head never jumps more than one
+    // cell along either axis and the knots keep following it correctly.
+    pub fn make_move(&mut self, m: Move) {
+        if let Move::Rotate(quarter_turns) = m {
+            self.quarter_turns = (self.quarter_turns + quarter_turns).rem_euclid(4);
+            return;
+        }
+
+        let (x_total, y_total) = m.displacement(self.quarter_turns);
+        let step_count = i32::max(x_total.abs(), y_total.abs());
+        let (mut x_done, mut y_done) = (0, 0);
+        for step in 1..=step_count {
+            let x_target = rounded_fraction(x_total, step, step_count);
+            let y_target = rounded_fraction(y_total, step, step_count);
+            self.step(x_target - x_done, y_target - y_done);
+            (x_done, y_done) = (x_target, y_target);
+        }
+    }
+}
+
+// total * step / step_count, rounded to the nearest whole number with halves away from zero.
+// Worked out in i64 since total * step overflows i32 on moves longer than about 32k cells.
+fn rounded_fraction(total: i32, step: i32, step_count: i32) -> i32 {
+    let scaled = 2 * total as i64 * step as i64;
+    let rounded = (scaled.abs() + step_count as i64) / (2 * step_count as i64);
+    (rounded * scaled.signum()) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn run(moves: &[&str]) -> Rope {
+        let mut rope = Rope::default().with_trace();
+        for m in moves {
+            rope.make_move(Move::from_str(m).unwrap());
+        }
+        rope
+    }
+
+    #[test]
+    fn long_straight_moves_visit_every_cell() {
+        let rope = run(&["R 40000", "U 3"]);
+        assert_eq!(rope.coordinates[0], Coordinate { x: 40000, y: 3 });
+        assert_eq!(rope.coordinates[1], Coordinate { x: 40000, y: 2 });
+        // x = 0 to 39999 along the bottom, then two cells up the last column
+        assert_eq!(rope.visited_positions.len(), 40002);
+    }
+
+    #[test]
+    fn vector_moves_follow_the_closest_grid_line() {
+        let rope = run(&["V 3,-2"]);
+        let heads = rope.trace.unwrap().replay().map(|knots| knots[0]).collect::<Vec<_>>();
+        let expected = [(0, 0), (1, -1), (2, -1), (3, -2)].map(|(x, y)| Coordinate { x, y });
+        assert_eq!(heads, expected);
+        assert_eq!(rope.coordinates[1], Coordinate { x: 2, y: -2 });
+    }
+}
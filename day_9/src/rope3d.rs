@@ -0,0 +1,83 @@
+use std::{collections::HashSet, str::FromStr};
+
+use crate::{coordinate::Coordinate3, moves::GenericParseError};
+
+// Moves in three dimensions: L/R along x, U/D along y and F/B along z.
+#[derive(Clone, Copy, Debug)]
+pub enum Move3 {
+    X(i32),
+    Y(i32),
+    Z(i32)
+}
+
+impl FromStr for Move3 {
+    type Err = GenericParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut split_iter = s.split(' ');
+        let direction_char = split_iter.next().ok_or(GenericParseError)?;
+        let number = i32::from_str(split_iter.next().ok_or(GenericParseError)?).map_err(|_| GenericParseError)?;
+
+        match direction_char {
+            "L" => Ok(Move3::X(-number)),
+            "R" => Ok(Move3::X(number)),
+            "U" => Ok(Move3::Y(number)),
+            "D" => Ok(Move3::Y(-number)),
+            "F" => Ok(Move3::Z(number)),
+            "B" => Ok(Move3::Z(-number)),
+            _ => Err(GenericParseError)
+        }
+    }
+}
+
+// The same rope in three dimensions. Knots touch if they're within one cell along every axis,
+// so each knot has 26 neighbours, and every knot's visited cells are kept.
+#[derive(Debug)]
+pub struct Rope3 {
+    pub coordinates: Vec<Coordinate3>,
+    visited_positions: Vec<HashSet<Coordinate3>>
+}
+
+impl Rope3 {
+    pub fn new(len: usize) -> Self {
+        Rope3 {
+            coordinates: vec![Coordinate3::default(); len],
+            visited_positions: vec![HashSet::from([Coordinate3::default()]); len]
+        }
+    }
+
+    pub fn visited_positions(&self, knot_idx: usize) -> &HashSet<Coordinate3> {
+        &self.visited_positions[knot_idx]
+    }
+
+    fn step(&mut self, x_step: i32, y_step: i32, z_step: i32) {
+        self.coordinates[0].x += x_step;
+        self.coordinates[0].y += y_step;
+        self.coordinates[0].z += z_step;
+
+        for coord_indx in 1..self.coordinates.len() {
+            let (leader, follower) = (self.coordinates[coord_indx - 1], self.coordinates[coord_indx]);
+            let (x_diff, y_diff, z_diff) = (leader.x - follower.x, leader.y - follower.y, leader.z - follower.z);
+            if i32::abs(x_diff) > 1 || i32::abs(y_diff) > 1 || i32::abs(z_diff) > 1 {
+                self.coordinates[coord_indx].x += i32::clamp(x_diff, -1, 1);
+                self.coordinates[coord_indx].y += i32::clamp(y_diff, -1, 1);
+                self.coordinates[coord_indx].z += i32::clamp(z_diff, -1, 1);
+            }
+        }
+
+        for (visited, coordinate) in self.visited_positions.iter_mut().zip(&self.coordinates) {
+            visited.insert(*coordinate);
+        }
+    }
+
+    pub fn make_move(&mut self, m: Move3) {
+        let (total, unit) = match m {
+            Move3::X(total) => (total, (i32::signum(total), 0, 0)),
+            Move3::Y(total) => (total, (0, i32::signum(total), 0)),
+            Move3::Z(total) => (total, (0, 0, i32::signum(total)))
+        };
+        for _ in 0..i32::abs(total) {
+            self.step(unit.0, unit.1, unit.2);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use crate::coordinate::Coordinate;
+
+// Where one knot has been. A visit is counted whenever the knot arrives in a cell, including where
+// it starts, but not for steps it spends standing still.
+#[derive(Debug, Clone)]
+pub struct KnotStats {
+    pub origin: Coordinate,
+    pub visit_counts: HashMap<Coordinate, usize>,
+    // The 1-based step on which the knot first stood somewhere other than its origin
+    pub first_departure: Option<usize>,
+    last_position: Coordinate
+}
+
+impl KnotStats {
+    pub fn new(origin: Coordinate) -> Self {
+        KnotStats { origin, visit_counts: HashMap::from([(origin, 1)]), first_departure: None, last_position: origin }
+    }
+
+    pub fn record(&mut self, step: usize, position: Coordinate) {
+        if position == self.last_position {
+            return;
+        }
+        *self.visit_counts.entry(position).or_insert(0) += 1;
+        if self.first_departure.is_none() && position != self.origin {
+            self.first_departure = Some(step);
+        }
+        self.last_position = position;
+    }
+
+    pub fn distinct_cells(&self) -> usize {
+        self.visit_counts.len()
+    }
+
+    // The smallest and largest x and y the knot has reached.
+    pub fn bounding_box(&self) -> (Coordinate, Coordinate) {
+        self.visit_counts.keys().fold((self.origin, self.origin), |(min, max), position| (
+            Coordinate { x: min.x.min(position.x), y: min.y.min(position.y) },
+            Coordinate { x: max.x.max(position.x), y: max.y.max(position.y) }
+        ))
+    }
+
+    // The cell visited most often, taking the smallest coordinate on ties so the answer is stable.
+    pub fn most_visited(&self) -> (Coordinate, usize) {
+        self.visit_counts.iter()
+            .map(|(position, count)| (*position, *count))
+            .min_by_key(|(position, count)| (usize::MAX - count, *position))
+            .unwrap()
+    }
+}
@@ -0,0 +1,141 @@
+use crate::coordinate::Coordinate;
+
+// Knots move at most one cell along each axis per step, so each knot's step is stored as one
+// byte holding (dx + 1) * 3 + (dy + 1). Anything bigger is written as JUMP and its new position
+// is kept in `jumps`, in order.
+const JUMP: u8 = 9;
+
+#[derive(Debug, Clone)]
+pub struct Trace {
+    initial: Vec<Coordinate>,
+    last: Vec<Coordinate>,
+    deltas: Vec<u8>,
+    jumps: Vec<Coordinate>
+}
+
+impl Trace {
+    pub fn new(initial: &[Coordinate]) -> Self {
+        Trace { initial: initial.to_vec(), last: initial.to_vec(), deltas: vec![], jumps: vec![] }
+    }
+
+    pub fn knot_count(&self) -> usize {
+        self.initial.len()
+    }
+
+    // The number of recorded steps, not counting the starting positions.
+    pub fn len(&self) -> usize {
+        self.deltas.len() / self.knot_count().max(1)
+    }
+
+    pub fn record(&mut self, knots: &[Coordinate]) {
+        for (previous, current) in self.last.iter_mut().zip(knots) {
+            let (dx, dy) = (current.x - previous.x, current.y - previous.y);
+            if dx.abs() <= 1 && dy.abs() <= 1 {
+                self.deltas.push(((dx + 1) * 3 + (dy + 1)) as u8);
+            }
+            else {
+                self.deltas.push(JUMP);
+                self.jumps.push(*current);
+            }
+            *previous = *current;
+        }
+    }
+
+    // Every frame of the trace, starting with the initial positions.
+    pub fn replay(&self) -> TraceReplay<'_> {
+        TraceReplay { trace: self, knots: None, delta_index: 0, jump_index: 0 }
+    }
+
+    // The smallest and largest coordinates any knot reaches.
+    pub fn bounds(&self) -> (Coordinate, Coordinate) {
+        self.replay().flatten().fold(
+            (self.initial[0], self.initial[0]),
+            |(min, max), knot| (
+                Coordinate { x: min.x.min(knot.x), y: min.y.min(knot.y) },
+                Coordinate { x: max.x.max(knot.x), y: max.y.max(knot.y) }
+            )
+        )
+    }
+}
+
+pub struct TraceReplay<'a> {
+    trace: &'a Trace,
+    knots: Option<Vec<Coordinate>>,
+    delta_index: usize,
+    jump_index: usize
+}
+
+impl<'a> Iterator for TraceReplay<'a> {
+    type Item = Vec<Coordinate>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let knots = match self.knots.as_mut() {
+            None => {
+                self.knots = Some(self.trace.initial.clone());
+                return self.knots.clone();
+            }
+            Some(knots) => knots
+        };
+
+        if self.delta_index >= self.trace.deltas.len() {
+            return None;
+        }
+
+        for knot in knots.iter_mut() {
+            let delta = self.trace.deltas[self.delta_index];
+            self.delta_index += 1;
+            if delta == JUMP {
+                *knot = self.trace.jumps[self.jump_index];
+                self.jump_index += 1;
+            }
+            else {
+                knot.x += delta as i32 / 3 - 1;
+                knot.y += delta as i32 % 3 - 1;
+            }
+        }
+        Some(knots.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{follow::{Elastic, FollowPolicy}, moves::Move, rope::Rope};
+
+    #[test]
+    fn replay_reproduces_every_recorded_frame() {
+        // Stiff enough that a stretched knot closes more than one cell at once
+        let policy = Elastic { slack: 1, stiffness: 0.8 };
+        let mut knots = vec![Coordinate { x: 3, y: -2 }; 6];
+        let mut trace = Trace::new(&knots);
+        let mut frames = vec![knots.clone()];
+        for step in 0..500 {
+            // An irregular walk that's the same on every run
+            let (dx, dy) = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)][(step * step / 7) % 7];
+            knots[0].x += dx;
+            knots[0].y += dy;
+            for idx in 1..knots.len() {
+                knots[idx] = policy.follow(knots[idx - 1], knots[idx]);
+            }
+            trace.record(&knots);
+            frames.push(knots.clone());
+        }
+
+        assert!(!trace.jumps.is_empty(), "nothing moved far enough to need a jump");
+        assert_eq!(trace.len(), 500);
+        assert_eq!(trace.replay().collect::<Vec<_>>(), frames);
+
+        // The same through a whole rope, where only the final frame can be checked directly
+        let mut rope = (1..10).fold(Rope::new(10).with_trace(), |rope, idx| rope.with_knot_policy(idx, Box::new(policy)));
+        for m in ["R 12", "UL 7", "D 9", "V -11,4", "CW 1", "R 5"] {
+            rope.make_move(Move::from_str(m).unwrap());
+        }
+        let trace = rope.trace.as_ref().unwrap();
+        assert!(!trace.jumps.is_empty());
+        let replayed = trace.replay().collect::<Vec<_>>();
+        assert_eq!(replayed.len(), rope.step_count + 1);
+        assert_eq!(replayed.last(), Some(&rope.coordinates));
+    }
+}